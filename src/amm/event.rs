@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct MigrateEvent {
+    pub user: Pubkey,
+    pub base_mint: Pubkey,
+    pub target: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub lp_amount: u64,
+    pub lp_burned: u64,
+    pub migration_fee: u64,
+    pub creator_reward: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapEvent {
+    pub user: Pubkey,
+    pub base_mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub is_buy: bool,
+    pub timestamp: i64,
+}
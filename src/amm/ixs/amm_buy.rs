@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{self, Mint, Token, TokenAccount, Transfer}, token_interface::{self, TokenInterface}};
+use crate::{
+    AmmState, MainState, SwapEvent,
+    error::PumpFunError,
+    math::{calculate_trading_fee, checked_sub},
+    utils::{close_token_account, get_transfer_fee, sync_native_amount, transfer_base},
+};
+
+pub fn amm_buy(ctx: Context<AAmmBuy>, amount /* lamports */: u64, min_amount_out: u64) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let amm_state = &mut ctx.accounts.amm_state;
+    let buyer = ctx.accounts.buyer.to_account_info();
+    let buyer_base_ata = &ctx.accounts.buyer_base_ata;
+    let buyer_quote_ata = &ctx.accounts.buyer_quote_ata;
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    // an existing WSOL balance is spent first and only the shortfall is wrapped
+    let held_wsol = buyer_quote_ata.amount > 0;
+    if amm_state.is_native_quote() {
+        sync_native_amount(buyer.clone(), buyer_quote_ata, amount, system_program.clone(), token_program.clone())?;
+    }
+
+    let fee = calculate_trading_fee(main_state.trading_fee, amount)?;
+    let input_amount = checked_sub(amount, fee)?;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let (base_amount, output_amount) = amm_state.compute_receivable_amount_on_buy(input_amount, transfer_fee.as_ref())?;
+    require!(output_amount >= min_amount_out, PumpFunError::SlippageExceeded);
+
+    // sending fee
+    let fee_transfer_cpi_account = Transfer{
+        from: buyer_quote_ata.to_account_info(),
+        to: ctx.accounts.fee_quote_ata.to_account_info(),
+        authority: buyer.clone()
+    };
+    token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), fee)?;
+
+    // sending input amount (sol)
+    let input_amount_transfer_cpi_account = Transfer{
+        from: buyer_quote_ata.to_account_info(),
+        to: ctx.accounts.amm_quote_vault.to_account_info(),
+        authority: buyer.clone()
+    };
+    token::transfer(CpiContext::new(token_program.clone(), input_amount_transfer_cpi_account), input_amount)?;
+
+    // sending tokens from amm vault (meme)
+    transfer_base(
+        ctx.accounts.amm_base_vault.to_account_info(),
+        buyer_base_ata.to_account_info(),
+        amm_state.to_account_info(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
+        base_amount,
+        &[&[
+            AmmState::PREFIX_SEED,
+            amm_state.base_mint.as_ref(),
+            amm_state.quote_mint.as_ref(),
+            &[ctx.bumps.amm_state]
+        ]],
+    )?;
+
+    // unwrap sol (or closing token account), unless the buyer already kept WSOL there
+    if amm_state.is_native_quote() && !held_wsol {
+        close_token_account(buyer.clone(), buyer_quote_ata.to_account_info(), token_program)?;
+    }
+
+    emit!(SwapEvent {
+        user: buyer.key(),
+        base_mint: amm_state.base_mint,
+        token_amount: output_amount,
+        sol_amount: amount,
+        base_reserves: amm_state.base_reserves,
+        quote_reserves: amm_state.quote_reserves,
+        is_buy: true,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AAmmBuy<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(mut, address = main_state.fee_recipient,)]
+    /// CHECK: this should be set by admin
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            AmmState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub amm_state: Box<Account<'info, AmmState>>,
+
+    #[account(address = amm_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = amm_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = base_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = base_token_program,
+    )]
+    pub buyer_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = amm_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub amm_base_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = amm_state,
+    )]
+    pub amm_quote_vault: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
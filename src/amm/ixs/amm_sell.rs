@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::{self, Mint, Token, TokenAccount, Transfer}, token_interface::{self, TokenInterface}};
+use crate::{
+    AmmState, MainState, SwapEvent,
+    error::PumpFunError,
+    math::{calculate_trading_fee, checked_sub},
+    utils::{close_token_account, get_transfer_fee, transfer_base},
+};
+
+pub fn amm_sell(ctx: Context<AAmmSell>, amount: u64, min_amount_out: u64) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let amm_state = &mut ctx.accounts.amm_state;
+    let seller = ctx.accounts.seller.to_account_info();
+    let seller_base_ata = &ctx.accounts.seller_base_ata;
+    let seller_quote_ata = &ctx.accounts.seller_quote_ata;
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let held_wsol = seller_quote_ata.amount > 0;
+
+    let input_amount = amount;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let _output_amount = amm_state.compute_receivable_amount_on_sell(input_amount, transfer_fee.as_ref())?;
+    let fee = calculate_trading_fee(main_state.trading_fee, _output_amount)?;
+    let output_amount = checked_sub(_output_amount, fee)?;
+    require!(output_amount >= min_amount_out, PumpFunError::SlippageExceeded);
+
+    // sending input amount (meme)
+    transfer_base(
+        seller_base_ata.to_account_info(),
+        ctx.accounts.amm_base_vault.to_account_info(),
+        seller.clone(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
+        input_amount,
+        &[],
+    )?;
+
+    let amm_seeds: &[&[u8]] = &[
+        AmmState::PREFIX_SEED,
+        amm_state.base_mint.as_ref(),
+        amm_state.quote_mint.as_ref(),
+        &[ctx.bumps.amm_state],
+    ];
+
+    // sending fee out of the gross output
+    let fee_transfer_cpi_account = Transfer{
+        from: ctx.accounts.amm_quote_vault.to_account_info(),
+        to: ctx.accounts.fee_quote_ata.to_account_info(),
+        authority: amm_state.to_account_info()
+    };
+    token::transfer(CpiContext::new_with_signer(token_program.clone(), fee_transfer_cpi_account, &[amm_seeds]), fee)?;
+
+    // sending sol from amm vault
+    let output_amount_transfer_cpi_account = Transfer{
+        from: ctx.accounts.amm_quote_vault.to_account_info(),
+        to: seller_quote_ata.to_account_info(),
+        authority: amm_state.to_account_info()
+    };
+    token::transfer(CpiContext::new_with_signer(token_program.clone(), output_amount_transfer_cpi_account, &[amm_seeds]), output_amount)?;
+
+    // unwrap sol (or closing token account), unless the seller already kept WSOL there
+    if amm_state.is_native_quote() && !held_wsol {
+        close_token_account(seller.clone(), seller_quote_ata.to_account_info(), token_program)?;
+    }
+
+    emit!(SwapEvent {
+        user: seller.key(),
+        base_mint: amm_state.base_mint,
+        token_amount: amount,
+        sol_amount: output_amount,
+        base_reserves: amm_state.base_reserves,
+        quote_reserves: amm_state.quote_reserves,
+        is_buy: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AAmmSell<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(mut, address = main_state.fee_recipient,)]
+    /// CHECK: this should be set by admin
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            AmmState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub amm_state: Box<Account<'info, AmmState>>,
+
+    #[account(address = amm_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = amm_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = base_token_program,
+    )]
+    pub seller_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = quote_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = amm_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub amm_base_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = amm_state,
+    )]
+    pub amm_quote_vault: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,240 @@
+use crate::{
+    error::PumpFunError,
+    math::{calculate_transfer_fee, checked_sub, integer_sqrt, to_u64, OrOverflow},
+    utils::{get_transfer_fee, graduate, transfer_base, Graduation, GraduationAccounts},
+    AmmState, MainState, MigrateEvent, PoolState,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer},
+    token_interface::{self, TokenInterface},
+};
+
+pub fn migrate_to_amm(ctx: Context<AMigrateToAmm>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let payer = ctx.accounts.payer.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let pool = ctx.accounts.pool_state.to_account_info();
+    let Graduation { base_amount, quote_amount, migration_fee, creator_reward } = graduate(
+        GraduationAccounts {
+            pool: pool.clone(),
+            sol_vault: ctx.accounts.sol_vault.to_account_info(),
+            base_mint: ctx.accounts.base_mint.to_account_info(),
+            reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+            reserver_quote_ata: ctx.accounts.reserver_quote_ata.to_account_info(),
+            fee_quote_ata: ctx.accounts.fee_quote_ata.to_account_info(),
+            creator_quote_ata: ctx.accounts.creator_quote_ata.to_account_info(),
+            token_program: token_program.clone(),
+            base_token_program: ctx.accounts.base_token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        &mut ctx.accounts.pool_state,
+        main_state,
+        ctx.bumps.pool_state,
+        ctx.bumps.sol_vault,
+    )?;
+
+    let pool_state = &ctx.accounts.pool_state;
+    // the vault only gets what is left after a token-2022 transfer fee
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let vault_base_amount = checked_sub(base_amount, calculate_transfer_fee(transfer_fee.as_ref(), base_amount)?)?;
+    let lp_amount = to_u64(integer_sqrt((vault_base_amount as u128).checked_mul(quote_amount as u128).or_overflow()?))?;
+
+    let pool_seeds: &[&[u8]] = &[
+        PoolState::PREFIX_SEED,
+        pool_state.base_mint.as_ref(),
+        pool_state.quote_mint.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+
+    // seeding amm at the price the curve closed at
+    transfer_base(
+        ctx.accounts.reserver_base_ata.to_account_info(),
+        ctx.accounts.amm_base_vault.to_account_info(),
+        pool.clone(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
+        base_amount,
+        &[pool_seeds],
+    )?;
+
+    let quote_transfer_cpi_account = Transfer{
+        from: ctx.accounts.reserver_quote_ata.to_account_info(),
+        to: ctx.accounts.amm_quote_vault.to_account_info(),
+        authority: pool.clone()
+    };
+    token::transfer(CpiContext::new_with_signer(token_program.clone(), quote_transfer_cpi_account, &[pool_seeds]), quote_amount)?;
+
+    let amm_state = &mut ctx.accounts.amm_state;
+    amm_state.pool = pool.key();
+    amm_state.base_mint = pool_state.base_mint;
+    amm_state.quote_mint = pool_state.quote_mint;
+    amm_state.lp_mint = ctx.accounts.lp_mint.key();
+    amm_state.base_reserves = vault_base_amount;
+    amm_state.quote_reserves = quote_amount;
+    amm_state.lp_supply = lp_amount;
+
+    let amm_seeds: &[&[u8]] = &[
+        AmmState::PREFIX_SEED,
+        amm_state.base_mint.as_ref(),
+        amm_state.quote_mint.as_ref(),
+        &[ctx.bumps.amm_state],
+    ];
+
+    // minting lp tokens and burning them right away, the liquidity stays locked forever
+    let mint_to_cpi_account = MintTo{
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        to: ctx.accounts.lp_lock_ata.to_account_info(),
+        authority: amm_state.to_account_info()
+    };
+    token::mint_to(CpiContext::new_with_signer(token_program.clone(), mint_to_cpi_account, &[amm_seeds]), lp_amount)?;
+
+    let lp_burn_cpi_account = Burn{
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        from: ctx.accounts.lp_lock_ata.to_account_info(),
+        authority: amm_state.to_account_info()
+    };
+    token::burn(CpiContext::new_with_signer(token_program, lp_burn_cpi_account, &[amm_seeds]), lp_amount)?;
+
+    emit!(MigrateEvent {
+        user: payer.key(),
+        base_mint: amm_state.base_mint,
+        target: amm_state.key(),
+        base_amount,
+        quote_amount,
+        lp_amount,
+        lp_burned: lp_amount,
+        migration_fee,
+        creator_reward,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AMigrateToAmm<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(mut, address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, address = main_state.fee_recipient,)]
+    /// CHECK: this should be set by admin
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, address = pool_state.owner)]
+    /// CHECK: pool creator, receives the graduation reward
+    pub creator: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            AmmState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+        space = 8 + AmmState::MAX_SIZE
+    )]
+    pub amm_state: Box<Account<'info, AmmState>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [AmmState::LP_MINT_SEED, amm_state.key().as_ref()],
+        bump,
+        mint::decimals = 9,
+        mint::authority = amm_state,
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = base_mint,
+        associated_token::authority = amm_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub amm_base_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = amm_state,
+    )]
+    pub amm_quote_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = lp_mint,
+        associated_token::authority = amm_state,
+    )]
+    pub lp_lock_ata: Box<Account<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
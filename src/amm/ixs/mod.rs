@@ -0,0 +1,8 @@
+pub mod migrate_to_amm;
+pub use migrate_to_amm::*;
+
+pub mod amm_buy;
+pub use amm_buy::*;
+
+pub mod amm_sell;
+pub use amm_sell::*;
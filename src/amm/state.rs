@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+use crate::{
+    constants::NATIVE_MINT_STR,
+    error::PumpFunError,
+    math::{calculate_output_amount, calculate_transfer_fee, checked_add, checked_sub, withdraw_reserve},
+};
+
+#[account]
+pub struct AmmState {
+    pub pool: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub lp_supply: u64,
+}
+
+impl AmmState {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"amm";
+    pub const LP_MINT_SEED: &'static [u8] = b"lp_mint";
+
+    pub fn is_native_quote(&self) -> bool {
+        self.quote_mint.to_string() == NATIVE_MINT_STR
+    }
+
+    // (base tokens leaving the vault, base tokens the buyer receives after the transfer fee)
+    pub fn compute_receivable_amount_on_buy(&mut self, quote_amount: u64, transfer_fee: Option<&TransferFee>) -> Result<(u64, u64)> {
+        require!(quote_amount > 0, PumpFunError::ZeroOutput);
+        let base_amount = calculate_output_amount(quote_amount, self.quote_reserves, self.base_reserves)?;
+        require!(base_amount > 0, PumpFunError::ZeroOutput);
+        self.base_reserves = withdraw_reserve(self.base_reserves, base_amount)?;
+        self.quote_reserves = checked_add(self.quote_reserves, quote_amount)?;
+        let received_amount = checked_sub(base_amount, calculate_transfer_fee(transfer_fee, base_amount)?)?;
+        require!(received_amount > 0, PumpFunError::ZeroOutput);
+        Ok((base_amount, received_amount))
+    }
+
+    pub fn compute_receivable_amount_on_sell(&mut self, base_amount: u64, transfer_fee: Option<&TransferFee>) -> Result<u64> {
+        let base_amount = checked_sub(base_amount, calculate_transfer_fee(transfer_fee, base_amount)?)?;
+        require!(base_amount > 0, PumpFunError::ZeroOutput);
+        let quote_amount = calculate_output_amount(base_amount, self.base_reserves, self.quote_reserves)?;
+        require!(quote_amount > 0, PumpFunError::ZeroOutput);
+        self.base_reserves = checked_add(self.base_reserves, base_amount)?;
+        self.quote_reserves = withdraw_reserve(self.quote_reserves, quote_amount)?;
+        Ok(quote_amount)
+    }
+}
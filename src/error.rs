@@ -24,5 +24,83 @@ pub enum PumpFunError {
     BondingCurveComplete,
 
     #[msg("Max Buy amount exceed")]
-    MaxBuyLimit
+    MaxBuyLimit,
+
+    #[msg("Pool already migrated")]
+    AlreadyMigrated,
+
+    #[msg("Dex program not allowed")]
+    DexNotAllowed,
+
+    #[msg("Invalid fee")]
+    InvalidFee,
+
+    #[msg("Reserve balance below bookkept reserves")]
+    ReserveMismatch,
+
+    #[msg("Curve not allowed or invalid")]
+    InvalidCurve,
+
+    #[msg("Curve invariant violated")]
+    CurveInvariantViolated,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+
+    #[msg("Zero output amount")]
+    ZeroOutput,
+
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+
+    #[msg("Price impact too high")]
+    PriceImpactTooHigh,
+
+    #[msg("Circuit breaker active")]
+    CircuitBreakerActive,
+
+    #[msg("Invalid price observation")]
+    InvalidObservation,
+
+    #[msg("Metadata field too long")]
+    MetadataTooLong,
+
+    #[msg("Base mint still has a freeze authority")]
+    FreezeAuthorityEnabled,
+
+    #[msg("Base mint still has a mint authority")]
+    MintAuthorityEnabled,
+
+    #[msg("Deposit doesn't cover the token supply")]
+    IncompleteSupply,
+
+    #[msg("Unsupported mint extension")]
+    UnsupportedMintExtension,
+
+    #[msg("Quote token account missing")]
+    MissingQuoteAccount,
+
+    #[msg("Invalid sell percentage")]
+    InvalidPercentage,
+
+    #[msg("Output below the minimum amount")]
+    SlippageExceeded,
+
+    #[msg("Dex did not take the migrated reserves")]
+    MigrationIncomplete,
+
+    #[msg("Dex returned no lp tokens")]
+    NoLpReceived,
+
+    #[msg("Virtual quote reserves must be positive")]
+    InvalidVirtualReserves,
+
+    #[msg("Completion threshold must be positive")]
+    InvalidCompleteThreshold,
+
+    #[msg("Price impact limit above 100%")]
+    InvalidPriceImpactLimit,
+
+    #[msg("Invalid circuit breaker config")]
+    InvalidCircuitBreaker,
 }
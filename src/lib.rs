@@ -1,16 +1,24 @@
 #![allow(unused)]
+#![allow(clippy::too_many_arguments)]
 
 use anchor_lang::prelude::*;
 
 pub mod main_state;
 pub mod pool;
+pub mod amm;
+pub mod migration;
+pub mod position;
 
 pub mod constants;
 pub mod error;
+pub mod math;
 pub mod utils;
 
 use main_state::*;
 use pool::*;
+use amm::*;
+use migration::*;
+use position::*;
 
 declare_id!("5BXzjtQpmqdXeDNmThjDYHsjFGviDCeW58SpumTW86Fa");
 
@@ -26,11 +34,19 @@ pub mod pump_fun {
         main_state::update_main_state(ctx, input)
     }
 
+    pub fn set_quote_config(ctx: Context<ASetQuoteConfig>, input: SetQuoteConfigInput) -> Result<()> {
+        main_state::set_quote_config(ctx, input)
+    }
+
     
     pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<()> {
         pool::create_pool(ctx, input)
     }
 
+    pub fn create_token_and_pool(ctx: Context<ACreateTokenAndPool>, input: CreateTokenAndPoolInput) -> Result<()> {
+        pool::create_token_and_pool(ctx, input)
+    }
+
     pub fn buy(ctx: Context<ABuy>, amount: u64) -> Result<()> {
         pool::buy(ctx, amount)
     }
@@ -38,8 +54,56 @@ pub mod pump_fun {
     pub fn sell(ctx: Context<ASell>, amount: u64) -> Result<()> {
         pool::sell(ctx, amount)
     }
-    
-    pub fn withdraw(ctx: Context<AWithdrawState>) -> Result<()> {
-        pool::withdraw(ctx)
+
+    pub fn sell_with_min_out(ctx: Context<ASell>, amount: u64, min_quote_out: u64) -> Result<()> {
+        pool::sell_with_min_out(ctx, amount, min_quote_out)
+    }
+
+    pub fn sell_percent(ctx: Context<ASell>, bps: u64, min_quote_out: u64) -> Result<()> {
+        pool::sell_percent(ctx, bps, min_quote_out)
+    }
+
+    pub fn sell_all(ctx: Context<ASell>, close_base_ata: bool, min_quote_out: u64) -> Result<()> {
+        pool::sell_all(ctx, close_base_ata, min_quote_out)
+    }
+
+    pub fn sync_reserves(ctx: Context<ASyncReserves>) -> Result<ReserveDrift> {
+        pool::sync_reserves(ctx)
+    }
+
+    pub fn skim(ctx: Context<ASkim>) -> Result<()> {
+        pool::skim(ctx)
+    }
+
+    pub fn quote_buy(ctx: Context<AQuote>, amount: u64, with_referrer: bool) -> Result<TradeQuote> {
+        pool::quote_buy(ctx, amount, with_referrer)
+    }
+
+    pub fn quote_sell(ctx: Context<AQuote>, amount: u64, with_referrer: bool) -> Result<TradeQuote> {
+        pool::quote_sell(ctx, amount, with_referrer)
+    }
+
+    pub fn update_pool_config(ctx: Context<AUpdatePoolConfig>, input: UpdatePoolConfigInput) -> Result<()> {
+        pool::update_pool_config(ctx, input)
+    }
+
+    pub fn update_pool_metadata(ctx: Context<AUpdatePoolMetadata>, input: UpdatePoolMetadataInput) -> Result<()> {
+        pool::update_pool_metadata(ctx, input)
+    }
+
+    pub fn migrate_to_amm(ctx: Context<AMigrateToAmm>) -> Result<()> {
+        amm::migrate_to_amm(ctx)
+    }
+
+    pub fn amm_buy(ctx: Context<AAmmBuy>, amount: u64, min_amount_out: u64) -> Result<()> {
+        amm::amm_buy(ctx, amount, min_amount_out)
+    }
+
+    pub fn amm_sell(ctx: Context<AAmmSell>, amount: u64, min_amount_out: u64) -> Result<()> {
+        amm::amm_sell(ctx, amount, min_amount_out)
+    }
+
+    pub fn migrate_to_dex<'info>(ctx: Context<'_, '_, '_, 'info, AMigrateToDex<'info>>) -> Result<()> {
+        migration::migrate_to_dex(ctx)
     }
 }
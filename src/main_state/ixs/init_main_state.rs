@@ -1,6 +1,7 @@
 use crate::{
     constants::{TOTAL_SUPPLY, VIRT_SOL_RESERVE},
     MainState,
+    CurveKind,
     error::PumpFunError
 };
 use anchor_lang::prelude::*;
@@ -18,6 +19,7 @@ pub fn init_main_state(ctx: Context<AInitMainState>) -> Result<()> {
     state.init_virt_quote_reserves = VIRT_SOL_RESERVE;    // default: 28 SOL
     state.trading_fee = 1_000;   // default: 1%
     state.max_buy_limit = 1_000_000_000;
+    state.allowed_curves = CurveKind::ConstantProduct.flag();
     Ok(())
 }
 
@@ -3,3 +3,6 @@ pub use init_main_state::*;
 
 pub mod update_main_state_owner;
 pub use update_main_state_owner::*;
+
+pub mod set_quote_config;
+pub use set_quote_config::*;
@@ -0,0 +1,56 @@
+use crate::{constants::NATIVE_MINT_STR, error::PumpFunError, MainState, QuoteConfig};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy)]
+pub struct SetQuoteConfigInput {
+    enabled: bool,
+    init_virt_quote_reserves: u64,
+    complete_threshold: u64,
+    max_buy_limit: u64,
+    migration_fee: u64,
+    creator_reward: u64,
+}
+
+pub fn set_quote_config(ctx: Context<ASetQuoteConfig>, input: SetQuoteConfigInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+    require!(input.init_virt_quote_reserves > 0, PumpFunError::InvalidVirtualReserves);
+    require!(input.complete_threshold > 0, PumpFunError::InvalidCompleteThreshold);
+
+    let quote_config = &mut ctx.accounts.quote_config;
+    quote_config.quote_mint = ctx.accounts.quote_mint.key();
+    quote_config.enabled = input.enabled;
+    quote_config.init_virt_quote_reserves = input.init_virt_quote_reserves;
+    quote_config.complete_threshold = input.complete_threshold;
+    quote_config.max_buy_limit = input.max_buy_limit;
+    quote_config.migration_fee = input.migration_fee;
+    quote_config.creator_reward = input.creator_reward;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ASetQuoteConfig<'info> {
+    #[account(mut, address = main_state.owner @ PumpFunError::Unauthorised)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(constraint = quote_mint.key().to_string() != NATIVE_MINT_STR @ PumpFunError::UnknownToken)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [QuoteConfig::PREFIX_SEED, quote_mint.key().as_ref()],
+        bump,
+        space = 8 + QuoteConfig::MAX_SIZE
+    )]
+    pub quote_config: Box<Account<'info, QuoteConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
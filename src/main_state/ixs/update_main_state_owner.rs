@@ -1,16 +1,27 @@
-use crate::{error::PumpFunError, MainState};
+use crate::{constants::{BPS_DIV, MAX_DEX_PROGRAMS}, error::PumpFunError, CircuitBreakerMode, MainState};
 use anchor_lang::prelude::*;
 
 #[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy)]
 pub struct UpdateMainStateInput {
-    owner: Pubkey,
-    fee_recipient: Pubkey,
-    trading_fee: u64,
-    max_buy_limit: u64
+    pub owner: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub trading_fee: u64,
+    pub max_buy_limit: u64,
+    // CpiDexAdapter hard-codes the `global:migrate` discriminator and a fixed account order, only list dexes that match it
+    pub dex_allowlist: Option<[Pubkey; MAX_DEX_PROGRAMS]>,
+    pub migration_fee: Option<u64>,
+    pub migration_fee_bps: Option<u64>,
+    pub creator_reward: Option<u64>,
+    pub allowed_curves: Option<u8>,
+    pub init_virt_quote_reserves: Option<u64>,
+    pub max_price_impact_bps: Option<u64>,
+    pub circuit_breaker_threshold_bps: Option<u64>,
+    pub circuit_breaker_window_slots: Option<u64>,
+    pub circuit_breaker_cooldown_slots: Option<u64>,
+    pub circuit_breaker_mode: Option<CircuitBreakerMode>,
     // total_token_supply: Option<u64>,
     // init_virt_base_reserves: Option<u64>,
     // init_real_base_reserves: Option<u64>,
-    // init_virt_quote_reserves: Option<u64>,
 }
 
 pub fn update_main_state(
@@ -28,10 +39,30 @@ pub fn update_main_state(
     state.fee_recipient = input.fee_recipient;
     state.trading_fee = input.trading_fee;
     state.max_buy_limit = input.max_buy_limit;
+    state.dex_allowlist = input.dex_allowlist.unwrap_or(state.dex_allowlist);
+    state.migration_fee = input.migration_fee.unwrap_or(state.migration_fee);
+    state.migration_fee_bps = input.migration_fee_bps.unwrap_or(state.migration_fee_bps);
+    state.creator_reward = input.creator_reward.unwrap_or(state.creator_reward);
+    state.allowed_curves = input.allowed_curves.unwrap_or(state.allowed_curves);
+    state.init_virt_quote_reserves = input.init_virt_quote_reserves.unwrap_or(state.init_virt_quote_reserves);
+    state.max_price_impact_bps = input.max_price_impact_bps.unwrap_or(state.max_price_impact_bps);
+    state.circuit_breaker_threshold_bps = input.circuit_breaker_threshold_bps.unwrap_or(state.circuit_breaker_threshold_bps);
+    state.circuit_breaker_window_slots = input.circuit_breaker_window_slots.unwrap_or(state.circuit_breaker_window_slots);
+    state.circuit_breaker_cooldown_slots = input.circuit_breaker_cooldown_slots.unwrap_or(state.circuit_breaker_cooldown_slots);
+    state.circuit_breaker_mode = input.circuit_breaker_mode.unwrap_or(state.circuit_breaker_mode);
+    require!((state.migration_fee_bps as u128) <= BPS_DIV, PumpFunError::InvalidFee);
+    require!(state.init_virt_quote_reserves > 0, PumpFunError::InvalidVirtualReserves);
+    require!((state.max_price_impact_bps as u128) <= BPS_DIV, PumpFunError::InvalidPriceImpactLimit);
+    require!((state.circuit_breaker_threshold_bps as u128) <= BPS_DIV, PumpFunError::InvalidCircuitBreaker);
+    // a zero threshold turns the breaker off, an armed breaker needs a window to measure and a cooldown to enforce
+    require!(
+        state.circuit_breaker_threshold_bps == 0
+            || (state.circuit_breaker_window_slots > 0 && state.circuit_breaker_cooldown_slots > 0),
+        PumpFunError::InvalidCircuitBreaker
+    );
     // state.total_token_supply = input.total_token_supply.unwrap_or(state.total_token_supply);
     // state.init_virt_base_reserves = input.init_virt_base_reserves.unwrap_or(state.init_virt_base_reserves);
     // state.init_real_base_reserves = input.init_real_base_reserves.unwrap_or(state.init_real_base_reserves);
-    // state.init_virt_quote_reserves = input.init_virt_quote_reserves.unwrap_or(state.init_virt_quote_reserves);
     // msg!("Updated mainState");
     
     Ok(())
@@ -1,4 +1,16 @@
 use anchor_lang::prelude::*;
+use crate::{
+    constants::{BPS_DIV, MAX_DEX_PROGRAMS},
+    math::{checked_sub, mul_div_ceil},
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitBreakerMode {
+    // only sells go through during the cooldown
+    SellOnly,
+    // nothing goes through during the cooldown
+    Halt,
+}
 
 #[account]
 pub struct MainState {
@@ -10,10 +22,51 @@ pub struct MainState {
     pub init_real_base_reserves: u64,
     pub init_virt_quote_reserves: u64,
     pub trading_fee: u64,
-    pub max_buy_limit: u64
+    pub max_buy_limit: u64,
+    pub dex_allowlist: [Pubkey; MAX_DEX_PROGRAMS],
+    pub migration_fee: u64,
+    pub migration_fee_bps: u64,
+    pub creator_reward: u64,
+    pub allowed_curves: u8,
+    pub max_price_impact_bps: u64,
+    pub circuit_breaker_threshold_bps: u64,
+    pub circuit_breaker_window_slots: u64,
+    pub circuit_breaker_cooldown_slots: u64,
+    pub circuit_breaker_mode: CircuitBreakerMode,
 }
 
 impl MainState {
     pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
     pub const PREFIX_SEED: &'static [u8] = b"main";
+
+    pub fn is_dex_allowed(&self, program_id: &Pubkey) -> bool {
+        program_id.ne(&Pubkey::default()) && self.dex_allowlist.contains(program_id)
+    }
+
+    // (protocol fee, creator reward) taken out of the quote reserves at graduation,
+    // `migration_fee` and `creator_reward` are absolute amounts in units of the pool quote
+    pub fn compute_graduation_fees(&self, quote_reserves: u64, migration_fee: u64, creator_reward: u64) -> Result<(u64, u64)> {
+        let bps_fee = mul_div_ceil(quote_reserves, self.migration_fee_bps, BPS_DIV)?;
+        let migration_fee = migration_fee.saturating_add(bps_fee).min(quote_reserves);
+        let creator_reward = creator_reward.min(checked_sub(quote_reserves, migration_fee)?);
+        Ok((migration_fee, creator_reward))
+    }
+}
+
+// allowlisted non-SOL quote mint, wrapped SOL keeps using the MainState values
+#[account]
+pub struct QuoteConfig {
+    pub quote_mint: Pubkey,
+    pub enabled: bool,
+    pub init_virt_quote_reserves: u64,
+    pub complete_threshold: u64,
+    pub max_buy_limit: u64,
+    // absolute graduation fees in quote units, the MainState ones are lamports
+    pub migration_fee: u64,
+    pub creator_reward: u64,
+}
+
+impl QuoteConfig {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"quote_config";
 }
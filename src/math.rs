@@ -0,0 +1,94 @@
+use crate::{constants::FEE_PER_DIV, error::PumpFunError};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+
+// rounding policy, always in favour of the pool:
+// - amounts paid out (curve output) round down
+// - amounts required from the user (curve input, fees) round up
+// - odd lamports of the referral split go to the protocol
+
+pub trait OrOverflow<T> {
+    fn or_overflow(self) -> Result<T>;
+}
+
+impl<T> OrOverflow<T> for Option<T> {
+    fn or_overflow(self) -> Result<T> {
+        self.ok_or_else(|| error!(PumpFunError::MathOverflow))
+    }
+}
+
+pub fn checked_add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).or_overflow()
+}
+
+pub fn checked_sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b).or_overflow()
+}
+
+pub fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).ok().or_overflow()
+}
+
+// a - b, failing when the difference doesn't fit in an i64
+pub fn signed_diff(a: u64, b: u64) -> Result<i64> {
+    i64::try_from(a as i128 - b as i128).ok().or_overflow()
+}
+
+// takes `amount` out of `reserve`, failing when the pool can't cover it
+pub fn withdraw_reserve(reserve: u64, amount: u64) -> Result<u64> {
+    reserve.checked_sub(amount).ok_or_else(|| error!(PumpFunError::InsufficientLiquidity))
+}
+
+pub fn mul_div(a: u64, b: u64, denominator: u128) -> Result<u64> {
+    to_u64((a as u128).checked_mul(b as u128).or_overflow()?.checked_div(denominator).or_overflow()?)
+}
+
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u128) -> Result<u64> {
+    require!(denominator > 0, PumpFunError::MathOverflow);
+    let product = (a as u128).checked_mul(b as u128).or_overflow()?;
+    to_u64(product.checked_add(denominator - 1).or_overflow()? / denominator)
+}
+
+pub fn calculate_trading_fee(fee: u64, amount: u64) -> Result<u64> {
+    mul_div_ceil(amount, fee, FEE_PER_DIV.checked_mul(100).or_overflow()?)
+}
+
+// base tokens the mint withholds when `amount` is transferred
+pub fn calculate_transfer_fee(transfer_fee: Option<&TransferFee>, amount: u64) -> Result<u64> {
+    match transfer_fee {
+        Some(transfer_fee) => transfer_fee.calculate_fee(amount).or_overflow(),
+        None => Ok(0),
+    }
+}
+
+// (protocol share, referrer share)
+pub fn split_referral_fee(fee: u64) -> (u64, u64) {
+    let referrer_fee = fee / 2;
+    (fee - referrer_fee, referrer_fee)
+}
+
+pub fn calculate_output_amount(input_amount: u64, input_reserve: u64, output_reserve: u64) -> Result<u64> {
+    let denominator = (input_reserve as u128).checked_add(input_amount as u128).or_overflow()?;
+    require!(denominator > 0, PumpFunError::InsufficientLiquidity);
+    mul_div(output_reserve, input_amount, denominator)
+}
+
+// input needed to take `output_amount` out of the reserves, rounded up
+pub fn calculate_input_amount(output_amount: u64, input_reserve: u64, output_reserve: u64) -> Result<u64> {
+    let denominator = (output_reserve as u128).checked_sub(output_amount as u128).or_overflow()?;
+    require!(denominator > 0, PumpFunError::InsufficientLiquidity);
+    mul_div_ceil(input_reserve, output_amount, denominator)
+}
+
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x / 2 + 1;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
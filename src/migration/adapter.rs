@@ -0,0 +1,103 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        hash::hash,
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+// pool_state signs so the dex can pull both reserves, the lp tokens go to pool_lp_ata
+pub struct MigrationAccounts<'a, 'info> {
+    pub pool_state: AccountInfo<'info>,
+    pub base_mint: AccountInfo<'info>,
+    pub quote_mint: AccountInfo<'info>,
+    pub reserver_base_ata: AccountInfo<'info>,
+    pub reserver_quote_ata: AccountInfo<'info>,
+    pub lp_mint: AccountInfo<'info>,
+    pub pool_lp_ata: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub base_token_program: AccountInfo<'info>,
+    pub remaining_accounts: &'a [AccountInfo<'info>],
+}
+
+pub trait MigrationAdapter<'info> {
+    fn migrate(
+        &self,
+        accounts: MigrationAccounts<'_, 'info>,
+        signer_seeds: &[&[u8]],
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<()>;
+}
+
+// anchor dex with a `migrate(base_amount, quote_amount)` taking the MigrationAccounts in order, then the remaining accounts
+pub struct CpiDexAdapter<'info> {
+    pub program: AccountInfo<'info>,
+}
+
+impl<'info> CpiDexAdapter<'info> {
+    pub const MIGRATE_IX_NAME: &'static str = "global:migrate";
+    pub const LP_MINT_SEED: &'static [u8] = b"lp_mint";
+
+    pub fn new(program: AccountInfo<'info>) -> Self {
+        Self { program }
+    }
+
+    fn instruction_data(base_amount: u64, quote_amount: u64) -> Vec<u8> {
+        let mut data = hash(Self::MIGRATE_IX_NAME.as_bytes()).to_bytes()[..8].to_vec();
+        data.extend_from_slice(&base_amount.to_le_bytes());
+        data.extend_from_slice(&quote_amount.to_le_bytes());
+        data
+    }
+}
+
+impl<'info> MigrationAdapter<'info> for CpiDexAdapter<'info> {
+    fn migrate(
+        &self,
+        accounts: MigrationAccounts<'_, 'info>,
+        signer_seeds: &[&[u8]],
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<()> {
+        let mut metas = vec![
+            AccountMeta::new(accounts.pool_state.key(), true),
+            AccountMeta::new_readonly(accounts.base_mint.key(), false),
+            AccountMeta::new_readonly(accounts.quote_mint.key(), false),
+            AccountMeta::new(accounts.reserver_base_ata.key(), false),
+            AccountMeta::new(accounts.reserver_quote_ata.key(), false),
+            AccountMeta::new(accounts.lp_mint.key(), false),
+            AccountMeta::new(accounts.pool_lp_ata.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+            AccountMeta::new_readonly(accounts.base_token_program.key(), false),
+        ];
+        let mut infos = vec![
+            accounts.pool_state,
+            accounts.base_mint,
+            accounts.quote_mint,
+            accounts.reserver_base_ata,
+            accounts.reserver_quote_ata,
+            accounts.lp_mint,
+            accounts.pool_lp_ata,
+            accounts.token_program,
+            accounts.base_token_program,
+        ];
+        for account in accounts.remaining_accounts {
+            metas.push(if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            });
+            infos.push(account.clone());
+        }
+        infos.push(self.program.clone());
+
+        let ix = Instruction {
+            program_id: self.program.key(),
+            accounts: metas,
+            data: Self::instruction_data(base_amount, quote_amount),
+        };
+        invoke_signed(&ix, &infos, &[signer_seeds])?;
+        Ok(())
+    }
+}
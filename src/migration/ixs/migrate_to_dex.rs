@@ -0,0 +1,211 @@
+use crate::{
+    error::PumpFunError,
+    migration::{CpiDexAdapter, MigrationAccounts, MigrationAdapter},
+    utils::{graduate, Graduation, GraduationAccounts},
+    MainState, MigrateEvent, PoolState,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{self, Burn, Mint, Token, TokenAccount},
+    token_interface::{self, TokenInterface},
+};
+
+pub fn migrate_to_dex<'info>(ctx: Context<'_, '_, '_, 'info, AMigrateToDex<'info>>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+    require!(main_state.is_dex_allowed(&ctx.accounts.dex_program.key()), PumpFunError::DexNotAllowed);
+
+    let payer = ctx.accounts.payer.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let pool = ctx.accounts.pool_state.to_account_info();
+    let Graduation { base_amount, quote_amount, migration_fee, creator_reward } = graduate(
+        GraduationAccounts {
+            pool: pool.clone(),
+            sol_vault: ctx.accounts.sol_vault.to_account_info(),
+            base_mint: ctx.accounts.base_mint.to_account_info(),
+            reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+            reserver_quote_ata: ctx.accounts.reserver_quote_ata.to_account_info(),
+            fee_quote_ata: ctx.accounts.fee_quote_ata.to_account_info(),
+            creator_quote_ata: ctx.accounts.creator_quote_ata.to_account_info(),
+            token_program: token_program.clone(),
+            base_token_program: ctx.accounts.base_token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        &mut ctx.accounts.pool_state,
+        main_state,
+        ctx.bumps.pool_state,
+        ctx.bumps.sol_vault,
+    )?;
+
+    let pool_state = &ctx.accounts.pool_state;
+    let pool_seeds: &[&[u8]] = &[
+        PoolState::PREFIX_SEED,
+        pool_state.base_mint.as_ref(),
+        pool_state.quote_mint.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+
+    // graduate burned, wrapped and paid out through cpis, so the reserve atas are read again
+    ctx.accounts.reserver_base_ata.reload()?;
+    ctx.accounts.reserver_quote_ata.reload()?;
+    let base_before = ctx.accounts.reserver_base_ata.amount;
+    let quote_before = ctx.accounts.reserver_quote_ata.amount;
+
+    let adapter = CpiDexAdapter::new(ctx.accounts.dex_program.to_account_info());
+    adapter.migrate(
+        MigrationAccounts {
+            pool_state: pool.clone(),
+            base_mint: ctx.accounts.base_mint.to_account_info(),
+            quote_mint: ctx.accounts.quote_mint.to_account_info(),
+            reserver_base_ata: ctx.accounts.reserver_base_ata.to_account_info(),
+            reserver_quote_ata: ctx.accounts.reserver_quote_ata.to_account_info(),
+            lp_mint: ctx.accounts.lp_mint.to_account_info(),
+            pool_lp_ata: ctx.accounts.pool_lp_ata.to_account_info(),
+            token_program: token_program.clone(),
+            base_token_program: ctx.accounts.base_token_program.to_account_info(),
+            remaining_accounts: ctx.remaining_accounts,
+        },
+        pool_seeds,
+        base_amount,
+        quote_amount,
+    )?;
+
+    // the pool signed the cpi, so the dex must have pulled both reserves
+    ctx.accounts.reserver_base_ata.reload()?;
+    ctx.accounts.reserver_quote_ata.reload()?;
+    require!(
+        base_before.saturating_sub(ctx.accounts.reserver_base_ata.amount) >= base_amount
+            && quote_before.saturating_sub(ctx.accounts.reserver_quote_ata.amount) >= quote_amount,
+        PumpFunError::MigrationIncomplete
+    );
+
+    // the dex creates the lp mint and the pool lp ata in the cpi, both are read only now
+    let pool_lp_ata = TokenAccount::try_deserialize(&mut &ctx.accounts.pool_lp_ata.try_borrow_data()?[..])?;
+    require!(
+        ctx.accounts.pool_lp_ata.owner.eq(&token::ID)
+            && pool_lp_ata.mint.eq(&ctx.accounts.lp_mint.key())
+            && pool_lp_ata.owner.eq(&pool.key()),
+        PumpFunError::NoLpReceived
+    );
+
+    // burning every lp token the dex handed back to the pool, the liquidity stays locked forever
+    let lp_burned = pool_lp_ata.amount;
+    require!(lp_burned > 0, PumpFunError::NoLpReceived);
+    let lp_burn_cpi_account = Burn{
+        mint: ctx.accounts.lp_mint.to_account_info(),
+        from: ctx.accounts.pool_lp_ata.to_account_info(),
+        authority: pool.clone()
+    };
+    token::burn(CpiContext::new_with_signer(token_program.clone(), lp_burn_cpi_account, &[pool_seeds]), lp_burned)?;
+
+    emit!(MigrateEvent {
+        user: payer.key(),
+        base_mint: pool_state.base_mint,
+        target: ctx.accounts.dex_program.key(),
+        base_amount,
+        quote_amount,
+        lp_amount: lp_burned,
+        lp_burned,
+        migration_fee,
+        creator_reward,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AMigrateToDex<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(mut, address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, address = main_state.fee_recipient,)]
+    /// CHECK: this should be set by admin
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, address = pool_state.owner)]
+    /// CHECK: pool creator, receives the graduation reward
+    pub creator: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_quote_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(executable)]
+    /// CHECK: checked against main_state.dex_allowlist
+    pub dex_program: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [CpiDexAdapter::LP_MINT_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        seeds::program = dex_program.key(),
+    )]
+    /// CHECK: lp mint of the dex pool, created by the dex during the cpi
+    pub lp_mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&pool_state.key(), &lp_mint.key()),
+    )]
+    /// CHECK: pool lp ata, created by the dex during the cpi and checked once it returns
+    pub pool_lp_ata: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,2 @@
+pub mod migrate_to_dex;
+pub use migrate_to_dex::*;
@@ -0,0 +1,5 @@
+pub mod ixs;
+pub use ixs::*;
+
+pub mod adapter;
+pub use adapter::*;
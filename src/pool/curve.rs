@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use crate::{
+    constants::BPS_DIV,
+    error::PumpFunError,
+    math::{to_u64, OrOverflow},
+};
+
+// step prices are quote units per base unit, scaled by PRICE_PRECISION
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000;
+pub const MAX_CURVE_STEPS: u64 = 64;
+pub const MAX_STEPPED_PRICES: usize = 8;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveKind {
+    // x * y = k on (virt_quote + real_quote) and real_base
+    ConstantProduct,
+    // x * y = k on (virt_quote + real_quote) and (virt_base + real_base), pays out of real_base only
+    DualVirtualProduct,
+    // price grows by `increment` every `step_size` tokens sold
+    Linear { start_price: u64, step_size: u64, increment: u64 },
+    // price grows by `growth_bps` every `step_size` tokens sold
+    Exponential { start_price: u64, step_size: u64, growth_bps: u64 },
+    // explicit price for each `step_size` tranche, the last one is kept once the list runs out
+    Stepped { step_size: u64, prices: [u64; MAX_STEPPED_PRICES] },
+}
+
+impl CurveKind {
+    pub fn flag(&self) -> u8 {
+        match self {
+            CurveKind::ConstantProduct => 1 << 0,
+            CurveKind::Linear { .. } => 1 << 1,
+            CurveKind::Exponential { .. } => 1 << 2,
+            CurveKind::Stepped { .. } => 1 << 3,
+            CurveKind::DualVirtualProduct => 1 << 4,
+        }
+    }
+
+    pub fn is_valid(&self, supply: u64) -> bool {
+        match *self {
+            CurveKind::ConstantProduct | CurveKind::DualVirtualProduct => true,
+            CurveKind::Linear { start_price, step_size, .. } | CurveKind::Exponential { start_price, step_size, .. } => {
+                // prices only grow, so a computable last step bounds `increment` and `growth_bps` for the whole curve
+                start_price > 0
+                    && step_size > 0
+                    && steps_for(supply, step_size) <= MAX_CURVE_STEPS
+                    && self.step_price(steps_for(supply, step_size).saturating_sub(1)).is_ok()
+            }
+            CurveKind::Stepped { step_size, prices } => {
+                let len = prices.iter().position(|p| *p == 0).unwrap_or(MAX_STEPPED_PRICES);
+                step_size > 0
+                    && steps_for(supply, step_size) <= MAX_CURVE_STEPS
+                    && len > 0
+                    && prices[len..].iter().all(|p| *p == 0)
+                    && prices[..len].windows(2).all(|w| w[1] >= w[0])
+            }
+        }
+    }
+
+    pub fn step_size(&self) -> u64 {
+        match *self {
+            CurveKind::ConstantProduct | CurveKind::DualVirtualProduct => 0,
+            CurveKind::Linear { step_size, .. }
+            | CurveKind::Exponential { step_size, .. }
+            | CurveKind::Stepped { step_size, .. } => step_size,
+        }
+    }
+
+    // price of the `step`-th tranche, scaled by PRICE_PRECISION
+    pub fn step_price(&self, step: u64) -> Result<u128> {
+        match *self {
+            CurveKind::ConstantProduct | CurveKind::DualVirtualProduct => err!(PumpFunError::InvalidCurve),
+            CurveKind::Linear { start_price, increment, .. } => (increment as u128)
+                .checked_mul(step as u128)
+                .or_overflow()?
+                .checked_add(start_price as u128)
+                .or_overflow(),
+            CurveKind::Exponential { start_price, growth_bps, .. } => {
+                // start_price * (1 + growth)^step, by squaring in PRICE_PRECISION fixed point
+                let mut factor = (BPS_DIV + growth_bps as u128)
+                    .checked_mul(PRICE_PRECISION)
+                    .or_overflow()?
+                    / BPS_DIV;
+                let mut result = PRICE_PRECISION;
+                let mut exp = step;
+                while exp > 0 {
+                    if exp & 1 == 1 {
+                        result = result.checked_mul(factor).or_overflow()? / PRICE_PRECISION;
+                    }
+                    exp >>= 1;
+                    if exp > 0 {
+                        factor = factor.checked_mul(factor).or_overflow()? / PRICE_PRECISION;
+                    }
+                }
+                Ok((start_price as u128).checked_mul(result).or_overflow()? / PRICE_PRECISION)
+            }
+            CurveKind::Stepped { prices, .. } => {
+                let last = prices.iter().rposition(|p| *p > 0).ok_or(PumpFunError::InvalidCurve)?;
+                Ok(prices[(step as usize).min(last)] as u128)
+            }
+        }
+    }
+
+    // base tokens received for `quote_amount` when `sold` of `supply` tokens are already out
+    pub fn quote_buy(&self, sold: u64, supply: u64, quote_amount: u64) -> Result<u64> {
+        let step_size = self.step_size();
+        let mut remaining = quote_amount as u128;
+        let mut position = sold;
+        while remaining > 0 {
+            require!(position < supply, PumpFunError::InsufficientLiquidity);
+            let step = position / step_size;
+            let price = self.step_price(step)?;
+            let available = (step + 1).checked_mul(step_size).or_overflow()?.min(supply) - position;
+            let cost = (available as u128)
+                .checked_mul(price)
+                .or_overflow()?
+                .checked_add(PRICE_PRECISION - 1)
+                .or_overflow()?
+                / PRICE_PRECISION;
+            if remaining >= cost {
+                position += available;
+                remaining -= cost;
+            } else {
+                position += to_u64(remaining.checked_mul(PRICE_PRECISION).or_overflow()? / price)?;
+                remaining = 0;
+            }
+        }
+        Ok(position - sold)
+    }
+
+    // quote received for selling `base_amount` back when `sold` tokens are out
+    pub fn quote_sell(&self, sold: u64, base_amount: u64) -> Result<u64> {
+        require!(base_amount <= sold, PumpFunError::InsufficientLiquidity);
+        let step_size = self.step_size();
+        let mut remaining = base_amount;
+        let mut position = sold;
+        let mut quote_amount: u128 = 0;
+        while remaining > 0 {
+            let step = (position - 1) / step_size;
+            let price = self.step_price(step)?;
+            let amount = (position - step * step_size).min(remaining);
+            quote_amount = quote_amount
+                .checked_add((amount as u128).checked_mul(price).or_overflow()? / PRICE_PRECISION)
+                .or_overflow()?;
+            position -= amount;
+            remaining -= amount;
+        }
+        to_u64(quote_amount)
+    }
+}
+
+fn steps_for(supply: u64, step_size: u64) -> u64 {
+    supply.div_ceil(step_size)
+}
@@ -23,6 +23,16 @@ pub struct TradeEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct CircuitBreakerTripped {
+    pub base_mint: Pubkey,
+    pub reference_price: u128,
+    pub price: u128,
+    pub movement_bps: u64,
+    pub cooldown_until_slot: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct CompleteEvent {
     pub user: Pubkey,
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::PRICE_PRECISION;
+
+pub const TRADE_HISTORY_LEN: usize = 64;
+
+#[zero_copy]
+pub struct TradeRecord {
+    pub user: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    // execution price, scaled by PRICE_PRECISION and capped at u64::MAX
+    pub price: u64,
+    pub slot: u64,
+    pub is_buy: u8,
+    pub _padding: [u8; 7],
+}
+
+// last TRADE_HISTORY_LEN trades of a pool, oldest entry is overwritten first
+#[account(zero_copy)]
+pub struct TradeHistory {
+    pub pool: Pubkey,
+    pub head: u64,
+    pub count: u64,
+    pub trades: [TradeRecord; TRADE_HISTORY_LEN],
+}
+
+impl TradeHistory {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"history";
+
+    pub fn record(&mut self, user: Pubkey, base_amount: u64, quote_amount: u64, is_buy: bool) -> Result<()> {
+        // saturates instead of failing, the history must never block a trade
+        let price = match base_amount {
+            0 => 0,
+            _ => u64::try_from(quote_amount as u128 * PRICE_PRECISION / base_amount as u128).unwrap_or(u64::MAX),
+        };
+        self.push(TradeRecord {
+            user,
+            base_amount,
+            quote_amount,
+            price,
+            slot: Clock::get()?.slot,
+            is_buy: is_buy as u8,
+            _padding: [0; 7],
+        });
+        Ok(())
+    }
+
+    fn push(&mut self, record: TradeRecord) {
+        self.trades[self.head as usize] = record;
+        self.head = (self.head + 1) % TRADE_HISTORY_LEN as u64;
+        self.count = (self.count + 1).min(TRADE_HISTORY_LEN as u64);
+    }
+}
@@ -1,85 +1,107 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{self,Mint, Token, TokenAccount, Transfer}};
+use anchor_spl::{associated_token::AssociatedToken, token::{self, CloseAccount, Mint, Token, TokenAccount}, token_interface::{self, TokenInterface}};
 use crate::{
-    constants::REAL_SOL_THRESHOLD, 
-    MainState, PoolState, 
-    TradeEvent, CompleteEvent, 
+    MainState, PoolState, TradeHistory, UserPosition, 
+    TradeEvent, CircuitBreakerTripped, CompleteEvent, 
     error::PumpFunError, 
     main_state, 
-    utils::{calculate_trading_fee, close_token_account, sync_native_amount},
+    math::{checked_add, checked_sub},
+    utils::{get_transfer_fee, pay_trading_fee, required_quote_ata, sol_vault_balance, transfer_base, transfer_quote, QuoteAccounts},
 };
 use std::str::FromStr;
 
 pub fn buy(ctx:Context<ABuy>, amount /* lamports */: u64)->Result<()>{
     let spec_addr = Pubkey::from_str("1nc1nerator11111111111111111111111111111111").unwrap();
+    let quote_accounts = ctx.accounts.quote_accounts()?;
+    let spend_wsol = ctx.accounts.spends_wsol();
+    if spend_wsol {
+        ctx.accounts.unwrap_into_vault(amount, ctx.bumps.pool_state, ctx.bumps.sol_vault)?;
+    }
     let main_state = &mut ctx.accounts.main_state;
     require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
 
-    require!(amount <= main_state.max_buy_limit, PumpFunError::MaxBuyLimit);
-    
     let pool_state = &mut ctx.accounts.pool_state;
     require!(pool_state.complete.eq(&false), PumpFunError::BondingCurveComplete);
+    require!(amount <= pool_state.buy_limit(main_state), PumpFunError::MaxBuyLimit);
 
     let referrer = &ctx.accounts.fee_referrer;
     let buyer = ctx.accounts.buyer.to_account_info();
     let buyer_base_ata = &ctx.accounts.buyer_base_ata;
-    let buyer_quote_ata = &ctx.accounts.buyer_quote_ata;
-    let token_program = ctx.accounts.token_program.to_account_info();
-    let system_program = ctx.accounts.system_program.to_account_info();
-    
-    sync_native_amount(buyer.clone(), &buyer_quote_ata, amount, system_program.clone(), token_program.clone())?;
 
-    let fee = calculate_trading_fee(main_state.trading_fee, amount);
-    let input_amount = amount - fee;
-    let output_amount = pool_state.compute_receivable_amount_on_buy(input_amount);
+    let slot = Clock::get()?.slot;
+    pool_state.guard_circuit_breaker(main_state, slot, true)?;
+    pool_state.accumulate_price(Clock::get()?.unix_timestamp)?;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let quote = pool_state.apply_buy(main_state.trading_fee, amount, !referrer.key().eq(&spec_addr), transfer_fee.as_ref())?;
+    let fee = quote.fee;
+    let input_amount = checked_sub(amount, fee)?;
+    let output_amount = quote.amount_out;
+    pool_state.check_price_impact(&quote, main_state.max_price_impact_bps)?;
+    if let Some((reference_price, movement_bps)) = pool_state.update_circuit_breaker(main_state, slot)? {
+        emit!(CircuitBreakerTripped {
+            base_mint: pool_state.base_mint,
+            reference_price,
+            price: pool_state.breaker_reference_price,
+            movement_bps,
+            cooldown_until_slot: pool_state.breaker_cooldown_until_slot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
     
-    // sending fee
-    if(referrer.key().eq(&spec_addr)){
-        let fee_transfer_cpi_account = Transfer{
-            from: buyer_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_quote_ata.to_account_info(),
-            authority: buyer.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), fee)?;
+    // unwrapped SOL already sits in the vault, so the vault pays the fees
+    let pool_key = pool_state.key();
+    let vault_seeds: &[&[u8]] = &[PoolState::SOL_VAULT_SEED, pool_key.as_ref(), &[ctx.bumps.sol_vault]];
+    let (payer, payer_seeds) = if spend_wsol {
+        (quote_accounts.reserve.clone(), vec![vault_seeds])
     } else {
-        let half_fee_amount = fee.checked_div(2).unwrap() as u64;
-        let fee_transfer_cpi_account = Transfer{
-            from: buyer_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_quote_ata.to_account_info(),
-            authority: buyer.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), half_fee_amount)?;
-        let fee_transfer_cpi_account = Transfer{
-            from: buyer_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_referrer_ata.to_account_info(),
-            authority: buyer.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), half_fee_amount)?;
-    }
+        (quote_accounts.user.clone(), vec![])
+    };
+
+    // sending fee, the referrer share is zero without a referrer
+    let referrer_fee = quote.referrer_fee;
+    let protocol_fee = checked_sub(fee, referrer_fee)?;
+    pay_trading_fee(&quote_accounts, payer.clone(), buyer.clone(), protocol_fee, referrer_fee, &payer_seeds)?;
     
     // sending input amount (sol)
-    let input_amount_transfer_cpi_account = Transfer{
-        from: buyer_quote_ata.to_account_info(),
-        to: ctx.accounts.reserver_quote_ata.to_account_info(),
-        authority: buyer.clone()
-    };
-    token::transfer(CpiContext::new(token_program.clone(), input_amount_transfer_cpi_account), input_amount)?;
+    if !spend_wsol {
+        transfer_quote(payer, quote_accounts.reserve.clone(), buyer.clone(), quote_accounts.program, input_amount, &[])?;
+    }
     
-    // sending tokens from reserve ata (meme)
-    let output_amount_transfer_cpi_account = Transfer{
-        from: ctx.accounts.reserver_base_ata.to_account_info(),
-        to: buyer_base_ata.to_account_info(),
-        authority: pool_state.to_account_info()
+    // sending tokens from reserve ata (meme), the transfer fee is withheld on the way
+    transfer_base(
+        ctx.accounts.reserver_base_ata.to_account_info(),
+        buyer_base_ata.to_account_info(),
+        pool_state.to_account_info(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
+        checked_add(output_amount, quote.base_transfer_fee)?,
+        &[&[
+            PoolState::PREFIX_SEED,
+            pool_state.base_mint.as_ref(),
+            pool_state.quote_mint.as_ref(),
+            &[ctx.bumps.pool_state]
+        ]],
+    )?;
+
+    ctx.accounts.reserver_base_ata.reload()?;
+    let quote_balance = if pool_state.is_native_quote() {
+        sol_vault_balance(&quote_accounts.reserve)?
+    } else {
+        token::accessor::amount(&quote_accounts.reserve)?
     };
-    token::transfer(CpiContext::new_with_signer(token_program.clone(), output_amount_transfer_cpi_account, &[&[
-        PoolState::PREFIX_SEED,
-        pool_state.base_mint.as_ref(),
-        pool_state.quote_mint.as_ref(),
-        &[ctx.bumps.pool_state]
-    ]]), output_amount)?;
+    pool_state.check_reserves(ctx.accounts.reserver_base_ata.amount, quote_balance)?;
 
-    // unwrap sol (or closing token account)
-    close_token_account(buyer.clone(), buyer_quote_ata.to_account_info(), token_program)?;
+    ctx.accounts.trade_history.load_mut()?.record(buyer.key(), output_amount, input_amount, true)?;
+
+    let user_position = &mut ctx.accounts.user_position;
+    let new_trader = !user_position.is_initialized();
+    if new_trader {
+        user_position.pool = pool_state.key();
+        user_position.owner = buyer.key();
+    }
+    user_position.record_buy(output_amount, amount)?;
+    pool_state.record_trade(amount, fee, true, new_trader)?;
 
     emit!(TradeEvent {
         user: buyer.key(), 
@@ -87,13 +109,13 @@ pub fn buy(ctx:Context<ABuy>, amount /* lamports */: u64)->Result<()>{
         // quote_mint: pool_state.quote_mint, 
         token_amount: output_amount, 
         sol_amount: amount, 
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves, 
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves, 
+        base_reserves: pool_state.bookkept_base_reserves()?, 
+        quote_reserves: pool_state.total_quote_reserves()?, 
         is_buy: true, 
         timestamp: Clock::get()?.unix_timestamp,
     });
 
-    if (pool_state.real_quote_reserves >= REAL_SOL_THRESHOLD) {
+    if (pool_state.real_quote_reserves >= pool_state.completion_threshold() || pool_state.real_base_reserves == 0) {
         pool_state.complete = true;
         
         emit!(CompleteEvent {
@@ -125,6 +147,8 @@ pub struct ABuy<'info> {
     #[account(mut)] 
     pub fee_referrer: AccountInfo<'info>,
 
+    // quote token accounts are only needed when the quote is not wrapped SOL,
+    // or with buyer_quote_ata and reserver_quote_ata to pay a wrapped SOL pool from a WSOL balance
     #[account(
         init_if_needed,
         payer = buyer,
@@ -132,7 +156,7 @@ pub struct ABuy<'info> {
         associated_token::authority = fee_referrer,
     )]
     /// CHECK: this should be set by fee_recipient
-    pub fee_referrer_ata: Box<Account<'info, TokenAccount>>,
+    pub fee_referrer_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
@@ -141,7 +165,7 @@ pub struct ABuy<'info> {
         associated_token::authority = fee_recipient,
     )]
     /// CHECK: this should be set by fee_recipient
-    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub fee_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         mut,
@@ -154,8 +178,31 @@ pub struct ABuy<'info> {
     )]
     pub pool_state: Box<Account<'info, PoolState>>,
 
+    #[account(
+        mut,
+        seeds = [TradeHistory::PREFIX_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub trade_history: AccountLoader<'info, TradeHistory>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [UserPosition::PREFIX_SEED, pool_state.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        space = 8 + UserPosition::MAX_SIZE
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
     #[account(address = pool_state.base_mint)]
-    pub base_mint: Box<Account<'info, Mint>>,
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(address = pool_state.quote_mint)]
     pub quote_mint: Box<Account<'info, Mint>>,
     
@@ -164,32 +211,100 @@ pub struct ABuy<'info> {
         payer = buyer,
         associated_token::mint = base_mint,
         associated_token::authority = buyer,
+        associated_token::token_program = base_token_program,
     )]
-    pub buyer_base_ata: Box<Account<'info, TokenAccount>>,
+    pub buyer_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = quote_mint,
         associated_token::authority = buyer,
     )]
-    pub buyer_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub buyer_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = base_mint,
         associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = quote_mint,
         associated_token::authority = pool_state,
     )]
-    pub reserver_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
+
+impl<'info> ABuy<'info> {
+    // a wrapped SOL pool takes WSOL instead of lamports when the buyer passes a quote ata
+    fn spends_wsol(&self) -> bool {
+        self.pool_state.is_native_quote() && self.buyer_quote_ata.is_some()
+    }
+
+    // moves `amount` WSOL into the pool ata and closes it into the vault, the vault refunds the ata rent to the buyer.
+    // the buyer's WSOL is never topped up from lamports and their ata stays open
+    fn unwrap_into_vault(&self, amount: u64, pool_bump: u8, vault_bump: u8) -> Result<()> {
+        let reserver_quote_ata = required_quote_ata(&self.reserver_quote_ata)?;
+        let sol_vault = self.sol_vault.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        transfer_quote(
+            required_quote_ata(&self.buyer_quote_ata)?,
+            reserver_quote_ata.clone(),
+            self.buyer.to_account_info(),
+            token_program.clone(),
+            amount,
+            &[],
+        )?;
+
+        let ata_rent = checked_sub(reserver_quote_ata.lamports(), token::accessor::amount(&reserver_quote_ata)?)?;
+        let close_cpi_accounts = CloseAccount {
+            account: reserver_quote_ata,
+            destination: sol_vault.clone(),
+            authority: self.pool_state.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(token_program, close_cpi_accounts, &[&[
+            PoolState::PREFIX_SEED,
+            self.pool_state.base_mint.as_ref(),
+            self.pool_state.quote_mint.as_ref(),
+            &[pool_bump]
+        ]]))?;
+
+        let pool_key = self.pool_state.key();
+        transfer_quote(
+            sol_vault.clone(),
+            self.buyer.to_account_info(),
+            sol_vault,
+            self.system_program.to_account_info(),
+            ata_rent,
+            &[&[PoolState::SOL_VAULT_SEED, pool_key.as_ref(), &[vault_bump]]],
+        )
+    }
+
+    fn quote_accounts(&self) -> Result<QuoteAccounts<'info>> {
+        if self.pool_state.is_native_quote() {
+            return Ok(QuoteAccounts {
+                user: self.buyer.to_account_info(),
+                fee: self.fee_recipient.to_account_info(),
+                referrer: self.fee_referrer.to_account_info(),
+                reserve: self.sol_vault.to_account_info(),
+                program: self.system_program.to_account_info(),
+            });
+        }
+        Ok(QuoteAccounts {
+            user: required_quote_ata(&self.buyer_quote_ata)?,
+            fee: required_quote_ata(&self.fee_quote_ata)?,
+            referrer: required_quote_ata(&self.fee_referrer_ata)?,
+            reserve: required_quote_ata(&self.reserver_quote_ata)?,
+            program: self.token_program.to_account_info(),
+        })
+    }
+}
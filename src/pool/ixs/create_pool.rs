@@ -1,20 +1,22 @@
 use crate::{
-    constants::NATIVE_MINT_STR,
-    MainState, PoolState,
-    CreateEvent,
+    MainState, PoolState, QuoteConfig, TradeHistory,
+    CreateEvent, CurveKind,
     error::PumpFunError,
-    utils::{check_balance_on_pool_creator, sync_native_amount},
+    math::{calculate_transfer_fee, checked_add, checked_sub},
+    utils::{check_balance_on_pool_creator, check_base_mint_extensions, get_transfer_fee, required_quote_ata, transfer_base, transfer_quote},
 };
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, SyncNative, Token, TokenAccount, Transfer},
+    token::{Mint, Token, TokenAccount},
+    token_interface::{self, TokenInterface},
 };
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
 pub struct CreatePoolInput {
     pub base_amount: u64,
     pub quote_amount: u64,
+    pub curve: CurveKind,
 }
 
 pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<()> {
@@ -26,64 +28,64 @@ pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<
     let system_program = ctx.accounts.system_program.to_account_info();
     let token_program = ctx.accounts.token_program.to_account_info();
     let creator_base_ata = &ctx.accounts.creator_base_ata;
-    let creator_quote_ata = &ctx.accounts.creator_quote_ata;
+
+    // no supply may live outside the curve, and the curve needs at least the configured supply
+    let supply = ctx.accounts.base_mint.supply;
+    require!(
+        input.base_amount == supply && supply >= main_state.total_token_supply,
+        PumpFunError::IncompleteSupply
+    );
+
+    check_base_mint_extensions(&ctx.accounts.base_mint.to_account_info())?;
+    // the reserve only gets what is left after a token-2022 transfer fee
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let base_amount = checked_sub(input.base_amount, calculate_transfer_fee(transfer_fee.as_ref(), input.base_amount)?)?;
 
     // require!(
     //     input.base_amount.gte(&main_state.init_real_base_reserves),
     //     PumpFunError::InsufficientFund
     // );
 
-    pool_state.owner = creator.key();
-    pool_state.base_mint = creator_base_ata.mint;
-    pool_state.quote_mint = creator_quote_ata.mint;
-    pool_state.real_base_reserves = main_state.init_real_base_reserves;
-    pool_state.virt_base_reserves = input.base_amount - main_state.init_real_base_reserves;
-    pool_state.real_quote_reserves = input.quote_amount;
-    pool_state.virt_quote_reserves = main_state.init_virt_quote_reserves;
-    pool_state.konst = (pool_state.real_base_reserves as u128)
-        .checked_mul((pool_state.virt_quote_reserves + pool_state.real_quote_reserves) as u128)
-        .unwrap();
-
-    //handler wrap sol
-    if (creator_base_ata.mint.to_string() == NATIVE_MINT_STR) {
-        sync_native_amount(
-            creator.clone(),
-            creator_base_ata,
-            input.base_amount,
-            system_program.clone(),
-            token_program.clone(),
-        )?;
-    }
-    //handler wrap sol
-    if (creator_quote_ata.mint.to_string() == NATIVE_MINT_STR) {
-        sync_native_amount(
-            creator.clone(),
-            creator_quote_ata,
-            input.quote_amount,
-            system_program.clone(),
-            token_program.clone(),
-        )?;
+    pool_state.initialize(
+        creator.key(),
+        creator_base_ata.mint,
+        ctx.accounts.quote_mint.key(),
+        main_state,
+        base_amount,
+        input.quote_amount,
+        input.curve,
+        ctx.accounts.quote_config.as_deref().map(|quote_config| &**quote_config),
+    )?;
+
+    // fails once the history is initialised, so a second create_pool keeps the recorded trades
+    if let Ok(mut trade_history) = ctx.accounts.trade_history.load_init() {
+        trade_history.pool = pool_state.key();
     }
 
     // //transfer
-    let base_transfer_cpi_accounts = Transfer {
-        from: ctx.accounts.creator_base_ata.to_account_info(),
-        to: ctx.accounts.reserver_base_ata.to_account_info(),
-        authority: creator.clone(),
-    };
-    token::transfer(
-        CpiContext::new(token_program.to_account_info(), base_transfer_cpi_accounts),
+    transfer_base(
+        ctx.accounts.creator_base_ata.to_account_info(),
+        ctx.accounts.reserver_base_ata.to_account_info(),
+        creator.clone(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
         input.base_amount,
+        &[],
     )?;
-    if(input.quote_amount > 0) {
-        let quote_transfer_cpi_accounts = Transfer {
-            from: ctx.accounts.creator_quote_ata.to_account_info(),
-            to: ctx.accounts.reserver_quote_ata.to_account_info(),
-            authority: creator.clone(),
-        };
-        token::transfer(
-            CpiContext::new(token_program.to_account_info(), quote_transfer_cpi_accounts),
+    if pool_state.is_native_quote() {
+        // the vault is funded up to its rent exempt minimum on top of the deposit
+        let sol_vault = ctx.accounts.sol_vault.to_account_info();
+        let vault_rent = Rent::get()?.minimum_balance(0).saturating_sub(sol_vault.lamports());
+        transfer_quote(creator.clone(), sol_vault, creator.clone(), system_program, checked_add(vault_rent, input.quote_amount)?, &[])?;
+    } else {
+        transfer_quote(
+            required_quote_ata(&ctx.accounts.creator_quote_ata)?,
+            required_quote_ata(&ctx.accounts.reserver_quote_ata)?,
+            creator.clone(),
+            token_program,
             input.quote_amount,
+            &[],
         )?;
     }
 
@@ -91,8 +93,8 @@ pub fn create_pool(ctx: Context<ACreatePool>, input: CreatePoolInput) -> Result<
         creator: pool_state.owner, 
         base_mint: pool_state.base_mint, 
         // quote_mint: pool_state.quote_mint, 
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves, 
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves, 
+        base_reserves: pool_state.bookkept_base_reserves()?, 
+        quote_reserves: pool_state.total_quote_reserves()?, 
         timestamp: Clock::get()?.unix_timestamp
     });
 
@@ -124,35 +126,65 @@ pub struct ACreatePool<'info> {
     )]
     pub pool_state: Box<Account<'info, PoolState>>,
 
-    pub base_mint: Box<Account<'info, Mint>>,
-    #[account(constraint = quote_mint.key().to_string() == NATIVE_MINT_STR @ PumpFunError::UnknownToken)]
+    #[account(
+        init_if_needed,
+        payer = creator,
+        seeds = [TradeHistory::PREFIX_SEED, pool_state.key().as_ref()],
+        bump,
+        space = 8 + TradeHistory::MAX_SIZE
+    )]
+    pub trade_history: AccountLoader<'info, TradeHistory>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        constraint = base_mint.freeze_authority.is_none() @ PumpFunError::FreezeAuthorityEnabled,
+        constraint = base_mint.mint_authority.is_none()
+            || base_mint.mint_authority == COption::Some(pool_state.key()) @ PumpFunError::MintAuthorityEnabled,
+    )]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     pub quote_mint: Box<Account<'info, Mint>>,
 
+    // required for any quote mint other than wrapped SOL
+    #[account(
+        seeds = [QuoteConfig::PREFIX_SEED, quote_mint.key().as_ref()],
+        bump,
+    )]
+    pub quote_config: Option<Box<Account<'info, QuoteConfig>>>,
+
     #[account(
         init_if_needed,
         payer=creator,
         associated_token::mint =base_mint,
         associated_token::authority = creator,
-        constraint = check_balance_on_pool_creator(creator_base_ata.as_ref(), input.base_amount) @ PumpFunError::InsufficientFund
+        associated_token::token_program = base_token_program,
+        constraint = check_balance_on_pool_creator(&creator_base_ata.mint, creator_base_ata.amount, input.base_amount) @ PumpFunError::InsufficientFund
     )]
-    pub creator_base_ata: Box<Account<'info, TokenAccount>>,
+    pub creator_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
         init_if_needed,
         payer=creator,
         associated_token::mint =quote_mint,
         associated_token::authority = creator,
-        constraint = check_balance_on_pool_creator(creator_quote_ata.as_ref(), input.quote_amount) @ PumpFunError::InsufficientFund
+        constraint = check_balance_on_pool_creator(&creator_quote_ata.mint, creator_quote_ata.amount, input.quote_amount) @ PumpFunError::InsufficientFund
     )]
-    pub creator_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub creator_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer=creator,
         associated_token::mint = base_mint,
         associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
         // constraint = reserver_base_ata.amount == 0
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    // quote token accounts are only needed when the quote is not wrapped SOL
     #[account(
         init_if_needed,
         payer = creator,
@@ -160,9 +192,10 @@ pub struct ACreatePool<'info> {
         associated_token::authority = pool_state,
         // constraint = reserver_quote_ata.amount == 0
     )]
-    pub reserver_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,211 @@
+use crate::{
+    MainState, PoolState, QuoteConfig, TradeHistory,
+    CreateEvent, CurveKind,
+    error::PumpFunError,
+    utils::transfer_quote,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{create_metadata_accounts_v3, mpl_token_metadata::types::DataV2, CreateMetadataAccountsV3, Metadata},
+    token::{Mint, Token, TokenAccount},
+    token_2022::spl_token_2022::instruction::AuthorityType,
+    token_interface::{self, MintTo, SetAuthority, TokenInterface},
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
+pub struct CreateTokenAndPoolInput {
+    pub decimals: u8,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub curve: CurveKind,
+}
+
+pub fn create_token_and_pool(ctx: Context<ACreateTokenAndPool>, input: CreateTokenAndPoolInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    let creator = ctx.accounts.creator.to_account_info();
+    let base_token_program = ctx.accounts.base_token_program.to_account_info();
+    let base_amount = main_state.total_token_supply;
+
+    pool_state.initialize(
+        creator.key(),
+        ctx.accounts.base_mint.key(),
+        ctx.accounts.quote_mint.key(),
+        main_state,
+        base_amount,
+        0,
+        input.curve,
+        ctx.accounts.quote_config.as_deref().map(|quote_config| &**quote_config),
+    )?;
+
+    ctx.accounts.trade_history.load_init()?.pool = pool_state.key();
+
+    // the vault starts at its rent exempt minimum so the first buy can land in it
+    if pool_state.is_native_quote() {
+        let sol_vault = ctx.accounts.sol_vault.to_account_info();
+        let vault_rent = Rent::get()?.minimum_balance(0).saturating_sub(sol_vault.lamports());
+        transfer_quote(creator.clone(), sol_vault, creator.clone(), ctx.accounts.system_program.to_account_info(), vault_rent, &[])?;
+    }
+
+    let pool_seeds: &[&[u8]] = &[
+        PoolState::PREFIX_SEED,
+        pool_state.base_mint.as_ref(),
+        pool_state.quote_mint.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+
+    // the whole supply goes straight to the reserve
+    let mint_to_cpi_accounts = MintTo {
+        mint: ctx.accounts.base_mint.to_account_info(),
+        to: ctx.accounts.reserver_base_ata.to_account_info(),
+        authority: pool_state.to_account_info(),
+    };
+    token_interface::mint_to(
+        CpiContext::new_with_signer(base_token_program.clone(), mint_to_cpi_accounts, &[pool_seeds]),
+        base_amount,
+    )?;
+
+    // immutable metadata, written while the pool still holds the mint authority
+    let metadata_cpi_accounts = CreateMetadataAccountsV3 {
+        metadata: ctx.accounts.metadata.to_account_info(),
+        mint: ctx.accounts.base_mint.to_account_info(),
+        mint_authority: pool_state.to_account_info(),
+        payer: creator.clone(),
+        update_authority: pool_state.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        rent: ctx.accounts.rent.to_account_info(),
+    };
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), metadata_cpi_accounts, &[pool_seeds]),
+        DataV2 {
+            name: input.name,
+            symbol: input.symbol,
+            uri: input.uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        false,
+        true,
+        None,
+    )?;
+
+    // nobody can mint more afterwards
+    let set_authority_cpi_accounts = SetAuthority {
+        current_authority: pool_state.to_account_info(),
+        account_or_mint: ctx.accounts.base_mint.to_account_info(),
+    };
+    token_interface::set_authority(
+        CpiContext::new_with_signer(base_token_program.clone(), set_authority_cpi_accounts, &[pool_seeds]),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    emit!(CreateEvent {
+        creator: pool_state.owner,
+        base_mint: pool_state.base_mint,
+        base_reserves: pool_state.bookkept_base_reserves()?,
+        quote_reserves: pool_state.total_quote_reserves()?,
+        timestamp: Clock::get()?.unix_timestamp
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(input: CreateTokenAndPoolInput)]
+pub struct ACreateTokenAndPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+        space = 8 + PoolState::MAX_SIZE
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [TradeHistory::PREFIX_SEED, pool_state.key().as_ref()],
+        bump,
+        space = 8 + TradeHistory::MAX_SIZE
+    )]
+    pub trade_history: AccountLoader<'info, TradeHistory>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    // no freeze authority, the mint authority is revoked once the supply is minted
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = input.decimals,
+        mint::authority = pool_state,
+        mint::token_program = base_token_program,
+    )]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    // required for any quote mint other than wrapped SOL
+    #[account(
+        seeds = [QuoteConfig::PREFIX_SEED, quote_mint.key().as_ref()],
+        bump,
+    )]
+    pub quote_config: Option<Box<Account<'info, QuoteConfig>>>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = base_mint,
+        associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    // only needed when the quote is not wrapped SOL
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    /// CHECK: created by the token metadata program
+    pub metadata: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub rent: Sysvar<'info, Rent>,
+}
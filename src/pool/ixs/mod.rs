@@ -1,11 +1,26 @@
 pub mod create_pool;
 pub use create_pool::*;
 
+pub mod create_token_and_pool;
+pub use create_token_and_pool::*;
+
 pub mod buy;
 pub use buy::*;
 
 pub mod sell;
 pub use sell::*;
 
-pub mod withdraw;
-pub use withdraw::*;
+pub mod sync_reserves;
+pub use sync_reserves::*;
+
+pub mod skim;
+pub use skim::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod update_pool_config;
+pub use update_pool_config::*;
+
+pub mod update_pool_metadata;
+pub use update_pool_metadata::*;
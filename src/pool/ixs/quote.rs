@@ -0,0 +1,55 @@
+use crate::{error::PumpFunError, utils::get_transfer_fee, MainState, PoolState, TradeQuote};
+use anchor_lang::prelude::*;
+use anchor_spl::{token::Mint, token_interface};
+
+// runs the buy math and checks on a copy of the pool, nothing is written
+pub fn quote_buy(ctx: Context<AQuote>, amount: u64, with_referrer: bool) -> Result<TradeQuote> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+    let mut pool_state = (**ctx.accounts.pool_state).clone();
+    require!(pool_state.complete.eq(&false), PumpFunError::BondingCurveComplete);
+    require!(amount <= pool_state.buy_limit(main_state), PumpFunError::MaxBuyLimit);
+    pool_state.guard_circuit_breaker(main_state, Clock::get()?.slot, true)?;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let quote = pool_state.apply_buy(main_state.trading_fee, amount, with_referrer, transfer_fee.as_ref())?;
+    pool_state.check_price_impact(&quote, main_state.max_price_impact_bps)?;
+    Ok(quote)
+}
+
+// runs the sell math and checks on a copy of the pool, nothing is written
+pub fn quote_sell(ctx: Context<AQuote>, amount: u64, with_referrer: bool) -> Result<TradeQuote> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let mut pool_state = (**ctx.accounts.pool_state).clone();
+    require!(pool_state.complete.eq(&false), PumpFunError::BondingCurveComplete);
+    pool_state.guard_circuit_breaker(main_state, Clock::get()?.slot, false)?;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let quote = pool_state.apply_sell(main_state.trading_fee, amount, with_referrer, transfer_fee.as_ref())?;
+    pool_state.check_price_impact(&quote, main_state.max_price_impact_bps)?;
+    Ok(quote)
+}
+
+#[derive(Accounts)]
+pub struct AQuote<'info> {
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+}
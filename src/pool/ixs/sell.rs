@@ -1,16 +1,55 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{self,Mint, Token, TokenAccount, Transfer}};
+use anchor_spl::{associated_token::AssociatedToken, token::{self, Mint, SyncNative, Token, TokenAccount}, token_interface::{self, CloseAccount, TokenInterface}};
 use crate::{
-    MainState, PoolState, 
-    TradeEvent, 
+    MainState, PoolState, TradeHistory, UserPosition, 
+    TradeEvent, CircuitBreakerTripped, 
     error::PumpFunError, 
     main_state, 
-    utils::{calculate_trading_fee, close_token_account, sync_native_amount}, 
+    constants::BPS_DIV,
+    math::{checked_add, checked_sub, mul_div},
+    utils::{get_transfer_fee, has_withheld_transfer_fees, pay_trading_fee, required_quote_ata, sol_vault_balance, transfer_base, transfer_quote, QuoteAccounts}, 
 };
 use std::str::FromStr;
 
-pub fn sell(ctx:Context<ASell>, amount: u64)->Result<()>{
+pub fn sell(mut ctx: Context<ASell>, amount: u64) -> Result<()> {
+    sell_base(&mut ctx, amount, 0)
+}
+
+// `sell` with a bound on what the seller receives after fees
+pub fn sell_with_min_out(mut ctx: Context<ASell>, amount: u64, min_quote_out: u64) -> Result<()> {
+    sell_base(&mut ctx, amount, min_quote_out)
+}
+
+// sells `bps` of the seller's base balance as it is on chain when the instruction runs
+pub fn sell_percent(mut ctx: Context<ASell>, bps: u64, min_quote_out: u64) -> Result<()> {
+    require!(bps > 0 && bps as u128 <= BPS_DIV, PumpFunError::InvalidPercentage);
+    let amount = mul_div(ctx.accounts.seller_base_ata.amount, bps, BPS_DIV)?;
+    sell_base(&mut ctx, amount, min_quote_out)
+}
+
+// sells the whole base balance, optionally closing the emptied ata to refund its rent
+pub fn sell_all(mut ctx: Context<ASell>, close_base_ata: bool, min_quote_out: u64) -> Result<()> {
+    let amount = ctx.accounts.seller_base_ata.amount;
+    sell_base(&mut ctx, amount, min_quote_out)?;
+
+    // an ata still holding withheld token-2022 fees can't be closed, it is left open
+    let seller_base_ata = ctx.accounts.seller_base_ata.to_account_info();
+    if close_base_ata && !has_withheld_transfer_fees(&seller_base_ata)? {
+        let seller = ctx.accounts.seller.to_account_info();
+        let close_cpi_accounts = CloseAccount {
+            account: seller_base_ata,
+            destination: seller.clone(),
+            authority: seller,
+        };
+        token_interface::close_account(CpiContext::new(ctx.accounts.base_token_program.to_account_info(), close_cpi_accounts))?;
+    }
+    Ok(())
+}
+
+// `min_quote_out` bounds what the seller receives after fees
+fn sell_base(ctx: &mut Context<ASell>, amount: u64, min_quote_out: u64) -> Result<()> {
     let spec_addr = Pubkey::from_str("1nc1nerator11111111111111111111111111111111").unwrap();
+    let quote_accounts = ctx.accounts.quote_accounts()?;
     let main_state = &mut ctx.accounts.main_state;
     require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
 
@@ -20,66 +59,99 @@ pub fn sell(ctx:Context<ASell>, amount: u64)->Result<()>{
     let referrer = &ctx.accounts.fee_referrer;
     let seller = ctx.accounts.seller.to_account_info();
     let seller_base_ata = &ctx.accounts.seller_base_ata;
-    let seller_quote_ata = &ctx.accounts.seller_quote_ata;
-    let token_program = ctx.accounts.token_program.to_account_info();
-    let system_program = ctx.accounts.system_program.to_account_info();
     
     // sync_native_amount(buyer.clone(), &buyer_quote_ata,amount, system_program.clone(), token_program.clone())?; 
     let input_amount = amount;
-    let _output_amount = pool_state.compute_receivable_amount_on_sell(input_amount);
-    let fee = calculate_trading_fee(main_state.trading_fee, _output_amount);
-    let output_amount = _output_amount - fee; 
-
-    sync_native_amount(seller.clone(), &seller_quote_ata, fee, system_program.clone(), token_program.clone())?;
-    
-    // sending fee
-    if(referrer.key().eq(&spec_addr)){
-        let fee_transfer_cpi_account = Transfer{
-            from: seller_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_quote_ata.to_account_info(),
-            authority: seller.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), fee)?;
-    } else {
-        let half_fee_amount = fee.checked_div(2).unwrap() as u64;
-        let fee_transfer_cpi_account = Transfer{
-            from: seller_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_quote_ata.to_account_info(),
-            authority: seller.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), half_fee_amount)?;
-
-         let fee_transfer_cpi_account = Transfer{
-            from: seller_quote_ata.to_account_info(),
-            to: ctx.accounts.fee_referrer_ata.to_account_info(),
-            authority: seller.clone()
-        };
-        token::transfer(CpiContext::new(token_program.clone(), fee_transfer_cpi_account), half_fee_amount)?;
+    let slot = Clock::get()?.slot;
+    pool_state.guard_circuit_breaker(main_state, slot, false)?;
+    pool_state.accumulate_price(Clock::get()?.unix_timestamp)?;
+    let transfer_fee = get_transfer_fee(&ctx.accounts.base_mint.to_account_info())?;
+    let quote = pool_state.apply_sell(main_state.trading_fee, input_amount, !referrer.key().eq(&spec_addr), transfer_fee.as_ref())?;
+    let fee = quote.fee;
+    let output_amount = quote.amount_out;
+    require!(output_amount >= min_quote_out, PumpFunError::SlippageExceeded);
+    pool_state.check_price_impact(&quote, main_state.max_price_impact_bps)?;
+    if let Some((reference_price, movement_bps)) = pool_state.update_circuit_breaker(main_state, slot)? {
+        emit!(CircuitBreakerTripped {
+            base_mint: pool_state.base_mint,
+            reference_price,
+            price: pool_state.breaker_reference_price,
+            movement_bps,
+            cooldown_until_slot: pool_state.breaker_cooldown_until_slot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
     }
-    
+
     // sending input amount (meme)
-    let input_amount_transfer_cpi_account = Transfer{
-        from: seller_base_ata.to_account_info(),
-        to: ctx.accounts.reserver_base_ata.to_account_info(),
-        authority: seller.clone()
-    };
-    token::transfer(CpiContext::new(token_program.clone(), input_amount_transfer_cpi_account), input_amount)?; 
+    transfer_base(
+        seller_base_ata.to_account_info(),
+        ctx.accounts.reserver_base_ata.to_account_info(),
+        seller.clone(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.base_mint.decimals,
+        ctx.accounts.base_token_program.to_account_info(),
+        input_amount,
+        &[],
+    )?;
     
-    // sending tokens from reserve ata(mame)
-    let output_amount_transfer_cpi_account = Transfer{
-        from: ctx.accounts.reserver_quote_ata.to_account_info(),
-        to: seller_quote_ata.to_account_info(),
-        authority: pool_state.to_account_info()
-    };
-    token::transfer(CpiContext::new_with_signer(token_program.clone(), output_amount_transfer_cpi_account,&[&[
+    // the reserve pays the gross output, signed by the vault or the pool
+    let pool_key = pool_state.key();
+    let native_quote = pool_state.is_native_quote();
+    let vault_seeds: &[&[u8]] = &[PoolState::SOL_VAULT_SEED, pool_key.as_ref(), &[ctx.bumps.sol_vault]];
+    let pool_seeds: &[&[u8]] = &[
         PoolState::PREFIX_SEED,
         pool_state.base_mint.as_ref(),
         pool_state.quote_mint.as_ref(),
         &[ctx.bumps.pool_state]
-    ]]), output_amount)?;
+    ];
+    let reserve_seeds = if native_quote { vault_seeds } else { pool_seeds };
 
-    // unwrap sol (or closing token account)
-    close_token_account(seller.clone(), seller_quote_ata.to_account_info(), token_program)?;
+    // sending fee out of the gross output, the referrer share is zero without a referrer
+    let referrer_fee = quote.referrer_fee;
+    let protocol_fee = checked_sub(fee, referrer_fee)?;
+    pay_trading_fee(&quote_accounts, quote_accounts.reserve.clone(), pool_state.to_account_info(), protocol_fee, referrer_fee, &[reserve_seeds])?;
+
+    // sending output amount (sol), on a wrapped SOL pool it stays wrapped when the seller passes a quote ata
+    let receive_wsol = native_quote && ctx.accounts.seller_quote_ata.is_some();
+    let destination = if receive_wsol {
+        required_quote_ata(&ctx.accounts.seller_quote_ata)?
+    } else {
+        quote_accounts.user
+    };
+    transfer_quote(
+        quote_accounts.reserve.clone(),
+        destination.clone(),
+        pool_state.to_account_info(),
+        quote_accounts.program,
+        output_amount,
+        &[reserve_seeds],
+    )?;
+    if receive_wsol {
+        let sync_accounts = SyncNative {
+            account: destination,
+        };
+        token::sync_native(CpiContext::new(ctx.accounts.token_program.to_account_info(), sync_accounts))?;
+    }
+
+    ctx.accounts.reserver_base_ata.reload()?;
+    let quote_balance = if native_quote {
+        sol_vault_balance(&quote_accounts.reserve)?
+    } else {
+        token::accessor::amount(&quote_accounts.reserve)?
+    };
+    pool_state.check_reserves(ctx.accounts.reserver_base_ata.amount, quote_balance)?;
+
+    let gross_output_amount = checked_add(output_amount, fee)?;
+    ctx.accounts.trade_history.load_mut()?.record(seller.key(), amount, gross_output_amount, false)?;
+
+    let user_position = &mut ctx.accounts.user_position;
+    let new_trader = !user_position.is_initialized();
+    if new_trader {
+        user_position.pool = pool_state.key();
+        user_position.owner = seller.key();
+    }
+    user_position.record_sell(amount, output_amount)?;
+    pool_state.record_trade(gross_output_amount, fee, false, new_trader)?;
 
     emit!(TradeEvent {
         user: seller.key(), 
@@ -87,8 +159,8 @@ pub fn sell(ctx:Context<ASell>, amount: u64)->Result<()>{
         // quote_mint: pool_state.quote_mint, 
         token_amount: amount, 
         sol_amount: output_amount, 
-        base_reserves: pool_state.real_base_reserves + pool_state.virt_base_reserves, 
-        quote_reserves: pool_state.virt_quote_reserves + pool_state.real_quote_reserves, 
+        base_reserves: pool_state.bookkept_base_reserves()?, 
+        quote_reserves: pool_state.total_quote_reserves()?, 
         is_buy: false, 
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -110,6 +182,8 @@ pub struct ASell<'info> {
     #[account(mut, address = main_state.fee_recipient,)]
     /// CHECK: this should be set by admin
     pub fee_recipient: AccountInfo<'info>,
+    // quote token accounts are only needed when the quote is not wrapped SOL,
+    // or seller_quote_ata to keep the proceeds of a wrapped SOL pool as WSOL
     #[account(
         init_if_needed,
         payer = seller,
@@ -117,7 +191,7 @@ pub struct ASell<'info> {
         associated_token::authority = fee_recipient,
     )]
     /// CHECK: this should be set by fee_recipient
-    pub fee_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub fee_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(mut)] 
     pub fee_referrer: AccountInfo<'info>,
@@ -129,7 +203,7 @@ pub struct ASell<'info> {
         associated_token::authority = fee_referrer,
     )]
     /// CHECK: this should be set by fee_recipient
-    pub fee_referrer_ata: Box<Account<'info, TokenAccount>>,
+    pub fee_referrer_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         mut,
@@ -142,8 +216,31 @@ pub struct ASell<'info> {
     )]
     pub pool_state: Box<Account<'info, PoolState>>,
 
+    #[account(
+        mut,
+        seeds = [TradeHistory::PREFIX_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub trade_history: AccountLoader<'info, TradeHistory>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        seeds = [UserPosition::PREFIX_SEED, pool_state.key().as_ref(), seller.key().as_ref()],
+        bump,
+        space = 8 + UserPosition::MAX_SIZE
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
     #[account(address = pool_state.base_mint)]
-    pub base_mint: Box<Account<'info, Mint>>,
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(address = pool_state.quote_mint)]
     pub quote_mint: Box<Account<'info, Mint>>,
     
@@ -152,32 +249,56 @@ pub struct ASell<'info> {
         payer = seller,
         associated_token::mint = base_mint,
         associated_token::authority = seller,
+        associated_token::token_program = base_token_program,
     )]
-    pub seller_base_ata: Box<Account<'info, TokenAccount>>,
+    pub seller_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = quote_mint,
         associated_token::authority = seller,
     )]
-    pub seller_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub seller_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = base_mint,
         associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
     )]
-    pub reserver_base_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = quote_mint,
         associated_token::authority = pool_state,
     )]
-    pub reserver_quote_ata: Box<Account<'info, TokenAccount>>,
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
+
+impl<'info> ASell<'info> {
+    fn quote_accounts(&self) -> Result<QuoteAccounts<'info>> {
+        if self.pool_state.is_native_quote() {
+            return Ok(QuoteAccounts {
+                user: self.seller.to_account_info(),
+                fee: self.fee_recipient.to_account_info(),
+                referrer: self.fee_referrer.to_account_info(),
+                reserve: self.sol_vault.to_account_info(),
+                program: self.system_program.to_account_info(),
+            });
+        }
+        Ok(QuoteAccounts {
+            user: required_quote_ata(&self.seller_quote_ata)?,
+            fee: required_quote_ata(&self.fee_quote_ata)?,
+            referrer: required_quote_ata(&self.fee_referrer_ata)?,
+            reserve: required_quote_ata(&self.reserver_quote_ata)?,
+            program: self.token_program.to_account_info(),
+        })
+    }
+}
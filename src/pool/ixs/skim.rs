@@ -0,0 +1,137 @@
+use crate::{
+    error::PumpFunError,
+    utils::{required_quote_ata, sol_vault_balance, transfer_base, transfer_quote},
+    MainState, PoolState,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+    token_interface::{self, TokenInterface},
+};
+
+pub fn skim(ctx: Context<ASkim>) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    let pool_state = &ctx.accounts.pool_state;
+    let pool_key = pool_state.key();
+    let pool_seeds: &[&[u8]] = &[
+        PoolState::PREFIX_SEED,
+        pool_state.base_mint.as_ref(),
+        pool_state.quote_mint.as_ref(),
+        &[ctx.bumps.pool_state],
+    ];
+    let vault_seeds: &[&[u8]] = &[PoolState::SOL_VAULT_SEED, pool_key.as_ref(), &[ctx.bumps.sol_vault]];
+
+    // (reserve, fee destination, program, signer seeds, reserve balance)
+    let (reserve, fee_destination, program, quote_seeds, quote_balance) = if pool_state.is_native_quote() {
+        (
+            ctx.accounts.sol_vault.to_account_info(),
+            ctx.accounts.fee_recipient.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            vault_seeds,
+            sol_vault_balance(&ctx.accounts.sol_vault)?,
+        )
+    } else {
+        let reserve = required_quote_ata(&ctx.accounts.reserver_quote_ata)?;
+        let quote_balance = token::accessor::amount(&reserve)?;
+        (
+            reserve,
+            required_quote_ata(&ctx.accounts.fee_quote_ata)?,
+            ctx.accounts.token_program.to_account_info(),
+            pool_seeds,
+            quote_balance,
+        )
+    };
+    let base_excess = ctx.accounts.reserver_base_ata.amount.saturating_sub(pool_state.bookkept_base_reserves()?);
+    let quote_excess = quote_balance.saturating_sub(pool_state.bookkept_quote_reserves()?);
+
+    if base_excess > 0 {
+        transfer_base(
+            ctx.accounts.reserver_base_ata.to_account_info(),
+            ctx.accounts.fee_base_ata.to_account_info(),
+            pool_state.to_account_info(),
+            ctx.accounts.base_mint.to_account_info(),
+            ctx.accounts.base_mint.decimals,
+            ctx.accounts.base_token_program.to_account_info(),
+            base_excess,
+            &[pool_seeds],
+        )?;
+    }
+    transfer_quote(reserve, fee_destination, pool_state.to_account_info(), program, quote_excess, &[quote_seeds])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ASkim<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(mut, address = main_state.fee_recipient,)]
+    /// CHECK: this should be set by admin
+    pub fee_recipient: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = base_token_program,
+    )]
+    pub fee_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    // quote token accounts are only needed when the quote is not wrapped SOL
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_recipient,
+    )]
+    pub fee_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub base_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,76 @@
+use crate::{error::PumpFunError, math::signed_diff, utils::sol_vault_balance, PoolState};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{Mint, TokenAccount},
+    token_interface::{self, TokenInterface},
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
+pub struct ReserveDrift {
+    pub base_balance: u64,
+    pub base_reserves: u64,
+    pub base_drift: i64,
+    pub quote_balance: u64,
+    pub quote_reserves: u64,
+    pub quote_drift: i64,
+}
+
+pub fn sync_reserves(ctx: Context<ASyncReserves>) -> Result<ReserveDrift> {
+    let pool_state = &ctx.accounts.pool_state;
+    let base_balance = ctx.accounts.reserver_base_ata.amount;
+    let quote_balance = if pool_state.is_native_quote() {
+        sol_vault_balance(&ctx.accounts.sol_vault)?
+    } else {
+        ctx.accounts.reserver_quote_ata.as_ref().ok_or(PumpFunError::MissingQuoteAccount)?.amount
+    };
+    let base_reserves = pool_state.bookkept_base_reserves()?;
+    let quote_reserves = pool_state.bookkept_quote_reserves()?;
+
+    Ok(ReserveDrift {
+        base_balance,
+        base_reserves,
+        base_drift: signed_diff(base_balance, base_reserves)?,
+        quote_balance,
+        quote_reserves,
+        quote_drift: signed_diff(quote_balance, quote_reserves)?,
+    })
+}
+
+#[derive(Accounts)]
+pub struct ASyncReserves<'info> {
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        seeds = [PoolState::SOL_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    #[account(address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = base_mint,
+        associated_token::authority = pool_state,
+        associated_token::token_program = base_token_program,
+    )]
+    pub reserver_base_ata: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    // only needed when the quote is not wrapped SOL
+    #[account(
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool_state,
+    )]
+    pub reserver_quote_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub base_token_program: Interface<'info, TokenInterface>,
+}
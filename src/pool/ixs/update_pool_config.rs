@@ -0,0 +1,51 @@
+use crate::{constants::BPS_DIV, error::PumpFunError, MainState, PoolState};
+use anchor_lang::prelude::*;
+use anchor_spl::{token::Mint, token_interface};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy)]
+pub struct UpdatePoolConfigInput {
+    // None falls back to main_state.max_price_impact_bps
+    max_price_impact_bps: Option<u64>,
+}
+
+pub fn update_pool_config(ctx: Context<AUpdatePoolConfig>, input: UpdatePoolConfigInput) -> Result<()> {
+    let main_state = &ctx.accounts.main_state;
+    require!(main_state.initialized.eq(&true), PumpFunError::Uninitialized);
+
+    if let Some(max_price_impact_bps) = input.max_price_impact_bps {
+        require!((max_price_impact_bps as u128) <= BPS_DIV, PumpFunError::InvalidPriceImpactLimit);
+    }
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.max_price_impact_bps = input.max_price_impact_bps;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AUpdatePoolConfig<'info> {
+    #[account(address = main_state.owner @ PumpFunError::Unauthorised)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [MainState::PREFIX_SEED],
+        bump,
+        has_one = owner,
+    )]
+    pub main_state: Box<Account<'info, MainState>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+}
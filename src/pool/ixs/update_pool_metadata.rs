@@ -0,0 +1,65 @@
+use crate::{error::PumpFunError, PoolMetadata, PoolState};
+use anchor_lang::prelude::*;
+use anchor_spl::{token::Mint, token_interface};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone)]
+pub struct UpdatePoolMetadataInput {
+    website: Option<String>,
+    twitter: Option<String>,
+    telegram: Option<String>,
+    description: Option<String>,
+}
+
+pub fn update_pool_metadata(ctx: Context<AUpdatePoolMetadata>, input: UpdatePoolMetadataInput) -> Result<()> {
+    let pool_state = &ctx.accounts.pool_state;
+    require!(pool_state.complete.eq(&false), PumpFunError::BondingCurveComplete);
+
+    let pool_metadata = &mut ctx.accounts.pool_metadata;
+    pool_metadata.pool = pool_state.key();
+    pool_metadata.website = input.website.unwrap_or(pool_metadata.website.clone());
+    pool_metadata.twitter = input.twitter.unwrap_or(pool_metadata.twitter.clone());
+    pool_metadata.telegram = input.telegram.unwrap_or(pool_metadata.telegram.clone());
+    pool_metadata.description = input.description.unwrap_or(pool_metadata.description.clone());
+
+    require!(
+        [&pool_metadata.website, &pool_metadata.twitter, &pool_metadata.telegram]
+            .iter()
+            .all(|link| link.len() <= PoolMetadata::MAX_LINK_LEN),
+        PumpFunError::MetadataTooLong
+    );
+    require!(pool_metadata.description.len() <= PoolMetadata::MAX_DESCRIPTION_LEN, PumpFunError::MetadataTooLong);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AUpdatePoolMetadata<'info> {
+    #[account(mut, address = pool_state.owner @ PumpFunError::Unauthorised)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [
+            PoolState::PREFIX_SEED,
+            base_mint.key().as_ref(),
+            quote_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        seeds = [PoolMetadata::PREFIX_SEED, pool_state.key().as_ref()],
+        bump,
+        space = 8 + PoolMetadata::MAX_SIZE
+    )]
+    pub pool_metadata: Box<Account<'info, PoolMetadata>>,
+
+    #[account(address = pool_state.base_mint)]
+    pub base_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(address = pool_state.quote_mint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+}
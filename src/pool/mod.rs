@@ -4,5 +4,17 @@ pub use ixs::*;
 pub mod state;
 pub use state::*;
 
+pub mod curve;
+pub use curve::*;
+
+pub mod history;
+pub use history::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod stats;
+pub use stats::*;
+
 pub mod event;
 pub use event::*;
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::{error::PumpFunError, math::OrOverflow, PoolState};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
+pub struct PriceObservation {
+    pub price_cumulative: u128,
+    pub timestamp: i64,
+}
+
+impl PoolState {
+    // adds spot_price * elapsed seconds to the accumulator, called before the reserves move
+    pub fn accumulate_price(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_price_update_ts);
+        if elapsed > 0 && self.last_price_update_ts != 0 {
+            let weighted_price = self.spot_price()?.checked_mul(elapsed as u128).or_overflow()?;
+            // wraps on purpose, only differences between observations are meaningful
+            self.price_cumulative = self.price_cumulative.wrapping_add(weighted_price);
+        }
+        self.last_price_update_ts = now;
+        Ok(())
+    }
+
+    // accumulator as it would read at `now` without a trade
+    pub fn observe(&self, now: i64) -> Result<PriceObservation> {
+        let mut pool_state = self.clone();
+        pool_state.accumulate_price(now)?;
+        Ok(PriceObservation {
+            price_cumulative: pool_state.price_cumulative,
+            timestamp: now,
+        })
+    }
+}
+
+// time weighted average price between two observations, scaled by PRICE_PRECISION
+pub fn compute_twap(start: &PriceObservation, end: &PriceObservation) -> Result<u128> {
+    require!(end.timestamp > start.timestamp, PumpFunError::InvalidObservation);
+    let elapsed = (end.timestamp - start.timestamp) as u128;
+    Ok(end.price_cumulative.wrapping_sub(start.price_cumulative) / elapsed)
+}
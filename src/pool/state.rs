@@ -1,4 +1,41 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+use crate::{
+    constants::{BPS_DIV, NATIVE_MINT_STR, REAL_SOL_THRESHOLD},
+    error::PumpFunError,
+    math::{calculate_output_amount, calculate_trading_fee, calculate_transfer_fee, checked_add, checked_sub, split_referral_fee, to_u64, withdraw_reserve, OrOverflow},
+    CircuitBreakerMode, CurveKind, MainState, QuoteConfig, PRICE_PRECISION,
+};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug)]
+pub struct TradeQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub referrer_fee: u64,
+    // base tokens withheld by a token-2022 transfer fee
+    pub base_transfer_fee: u64,
+    pub price_impact_bps: u64,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+}
+
+// socials shown next to the pool, editable by the creator until the curve completes
+#[account]
+pub struct PoolMetadata {
+    pub pool: Pubkey,
+    pub website: String,
+    pub twitter: String,
+    pub telegram: String,
+    pub description: String,
+}
+
+impl PoolMetadata {
+    pub const MAX_LINK_LEN: usize = 128;
+    pub const MAX_DESCRIPTION_LEN: usize = 512;
+    pub const MAX_SIZE: usize = 32 + 3 * (4 + Self::MAX_LINK_LEN) + 4 + Self::MAX_DESCRIPTION_LEN;
+    pub const PREFIX_SEED: &'static [u8] = b"pool_metadata";
+}
 
 #[account]
 pub struct PoolState {
@@ -10,35 +47,315 @@ pub struct PoolState {
     pub quote_mint: Pubkey,
     pub virt_quote_reserves: u64,
     pub real_quote_reserves: u64,
-    pub complete: bool
+    pub complete: bool,
+    pub migrated: bool,
+    pub curve: CurveKind,
+    pub init_real_base_reserves: u64,
+    pub max_price_impact_bps: Option<u64>,
+    pub breaker_reference_price: u128,
+    pub breaker_window_start_slot: u64,
+    pub breaker_cooldown_until_slot: u64,
+    pub price_cumulative: u128,
+    pub last_price_update_ts: i64,
+    pub total_buy_volume: u64,
+    pub total_sell_volume: u64,
+    pub trade_count: u64,
+    pub total_fees: u64,
+    pub unique_traders: u64,
+    pub ath_price: u128,
+    // taken from the QuoteConfig of a non-SOL quote, None follows the SOL defaults
+    pub complete_threshold: Option<u64>,
+    pub max_buy_limit: Option<u64>,
+    pub migration_fee: Option<u64>,
+    pub creator_reward: Option<u64>,
 }
 
 impl PoolState {
     pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
     pub const PREFIX_SEED: &'static [u8] = b"pool";
+    // system owned PDA holding the lamports of a wrapped SOL pool
+    pub const SOL_VAULT_SEED: &'static [u8] = b"sol_vault";
 
-    pub fn compute_receivable_amount_on_buy(&mut self, quote_amount: u64) -> u64 {
-        let base_amount =
-            calculate_output_amount(quote_amount, self.virt_quote_reserves + self.real_quote_reserves, self.real_base_reserves);
-        self.real_base_reserves -= base_amount;
-        self.real_quote_reserves += quote_amount;
-        base_amount
+    // sets up a fresh pool over `base_amount` deposited tokens, `init_real_base_reserves` of them are sold on the curve
+    pub fn initialize(
+        &mut self,
+        owner: Pubkey,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+        main_state: &MainState,
+        base_amount: u64,
+        quote_amount: u64,
+        curve: CurveKind,
+        quote_config: Option<&QuoteConfig>,
+    ) -> Result<()> {
+        require!(main_state.allowed_curves & curve.flag() != 0, PumpFunError::InvalidCurve);
+        require!(curve.is_valid(main_state.init_real_base_reserves), PumpFunError::InvalidCurve);
+
+        self.virt_quote_reserves = main_state.init_virt_quote_reserves;
+        self.complete_threshold = None;
+        self.max_buy_limit = None;
+        self.migration_fee = None;
+        self.creator_reward = None;
+        if quote_mint.to_string() != NATIVE_MINT_STR {
+            let quote_config = quote_config.ok_or(PumpFunError::UnknownToken)?;
+            require!(
+                quote_config.enabled && quote_config.quote_mint == quote_mint,
+                PumpFunError::UnknownToken
+            );
+            self.virt_quote_reserves = quote_config.init_virt_quote_reserves;
+            self.complete_threshold = Some(quote_config.complete_threshold);
+            self.max_buy_limit = Some(quote_config.max_buy_limit);
+            self.migration_fee = Some(quote_config.migration_fee);
+            self.creator_reward = Some(quote_config.creator_reward);
+        }
+
+        self.owner = owner;
+        self.base_mint = base_mint;
+        self.quote_mint = quote_mint;
+        self.real_base_reserves = main_state.init_real_base_reserves;
+        self.init_real_base_reserves = main_state.init_real_base_reserves;
+        self.curve = curve;
+        self.virt_base_reserves = checked_sub(base_amount, main_state.init_real_base_reserves)?;
+        self.real_quote_reserves = quote_amount;
+        self.konst = self.compute_konst()?;
+        self.last_price_update_ts = Clock::get()?.unix_timestamp;
+        Ok(())
     }
 
-    pub fn compute_receivable_amount_on_sell(&mut self, base_amount: u64) -> u64 {
-        let quote_amount =
-            calculate_output_amount(base_amount, self.real_base_reserves, self.virt_quote_reserves + self.real_quote_reserves);
-        self.real_base_reserves += base_amount;
-        self.real_quote_reserves -= quote_amount;
-        quote_amount
+    pub fn is_native_quote(&self) -> bool {
+        self.quote_mint.to_string() == NATIVE_MINT_STR
+    }
+
+    pub fn buy_limit(&self, main_state: &MainState) -> u64 {
+        self.max_buy_limit.unwrap_or(main_state.max_buy_limit)
+    }
+
+    // the curve completes once this much quote has been raised
+    pub fn completion_threshold(&self) -> u64 {
+        self.complete_threshold.unwrap_or(REAL_SOL_THRESHOLD)
+    }
+
+    // (protocol fee, creator reward) taken out of the quote reserves at graduation
+    pub fn graduation_fees(&self, main_state: &MainState) -> Result<(u64, u64)> {
+        main_state.compute_graduation_fees(
+            self.real_quote_reserves,
+            self.migration_fee.unwrap_or(main_state.migration_fee),
+            self.creator_reward.unwrap_or(main_state.creator_reward),
+        )
+    }
+
+    // tokens the reserve atas must hold at any time
+    pub fn bookkept_base_reserves(&self) -> Result<u64> {
+        checked_add(self.virt_base_reserves, self.real_base_reserves)
+    }
+
+    pub fn bookkept_quote_reserves(&self) -> Result<u64> {
+        Ok(self.real_quote_reserves)
+    }
+
+    // quote side of the curve, virtual reserves included
+    pub fn total_quote_reserves(&self) -> Result<u64> {
+        checked_add(self.virt_quote_reserves, self.real_quote_reserves)
+    }
+
+    pub fn check_reserves(&self, base_balance: u64, quote_balance: u64) -> Result<()> {
+        require!(base_balance >= self.bookkept_base_reserves()?, PumpFunError::ReserveMismatch);
+        require!(quote_balance >= self.bookkept_quote_reserves()?, PumpFunError::ReserveMismatch);
+        Ok(())
+    }
+
+    // tokens already sold out of the curve
+    pub fn sold_base_amount(&self) -> Result<u64> {
+        checked_sub(self.init_real_base_reserves, self.real_base_reserves)
     }
-}
 
-fn calculate_output_amount(input_amount: u64, input_reserve: u64, output_reserve: u64) -> u64 {
-    let output_amount = (output_reserve as u128)
-        .checked_mul(input_amount as u128)
-        .unwrap()
-        .checked_div((input_reserve as u128) + (input_amount as u128))
-        .unwrap();
-    output_amount as u64
+    // price of the next base unit, scaled by PRICE_PRECISION
+    pub fn spot_price(&self) -> Result<u128> {
+        let base_reserves = match self.curve {
+            CurveKind::ConstantProduct => self.real_base_reserves,
+            CurveKind::DualVirtualProduct => self.bookkept_base_reserves()?,
+            curve => return curve.step_price(self.sold_base_amount()? / curve.step_size()),
+        };
+        require!(base_reserves > 0, PumpFunError::InsufficientLiquidity);
+        Ok((self.total_quote_reserves()? as u128).checked_mul(PRICE_PRECISION).or_overflow()? / base_reserves as u128)
+    }
+
+    // price the last buyer paid, a sold out step curve has no next tranche to quote
+    pub fn closing_price(&self) -> Result<u128> {
+        match self.curve {
+            CurveKind::ConstantProduct | CurveKind::DualVirtualProduct => self.spot_price(),
+            curve => curve.step_price(self.sold_base_amount()?.saturating_sub(1) / curve.step_size()),
+        }
+    }
+
+    // base tokens that pair with `quote_amount` at the closing price, taken from the real reserves first
+    // and then from the held back tokens. the virtual quote has no tokens behind it, so seeding the real
+    // reserves alone would open the migration target below the price the curve closed at
+    pub fn graduation_base_amount(&self, quote_amount: u64) -> Result<u64> {
+        let price = self.closing_price()?;
+        require!(price > 0, PumpFunError::InsufficientLiquidity);
+        let base_amount = (quote_amount as u128).checked_mul(PRICE_PRECISION).or_overflow()? / price;
+        to_u64(base_amount.min(self.bookkept_base_reserves()? as u128))
+    }
+
+    // applies a buy of `amount` quote (fee included) and reports what happened,
+    // `amount_out` is what the buyer ends up with once the base transfer fee is withheld
+    pub fn apply_buy(&mut self, trading_fee: u64, amount: u64, with_referrer: bool, transfer_fee: Option<&TransferFee>) -> Result<TradeQuote> {
+        let price_before = self.spot_price()?;
+        let fee = calculate_trading_fee(trading_fee, amount)?;
+        let input_amount = checked_sub(amount, fee)?;
+        let (base_amount, output_amount) = self.compute_receivable_amount_on_buy(input_amount, transfer_fee)?;
+        let base_transfer_fee = checked_sub(base_amount, output_amount)?;
+        self.build_quote(amount, output_amount, fee, base_transfer_fee, with_referrer, price_before)
+    }
+
+    // applies a sell of `amount` base, the fee is part of the gross quote output
+    pub fn apply_sell(&mut self, trading_fee: u64, amount: u64, with_referrer: bool, transfer_fee: Option<&TransferFee>) -> Result<TradeQuote> {
+        let price_before = self.spot_price()?;
+        let gross_output_amount = self.compute_receivable_amount_on_sell(amount, transfer_fee)?;
+        let fee = calculate_trading_fee(trading_fee, gross_output_amount)?;
+        let output_amount = checked_sub(gross_output_amount, fee)?;
+        let base_transfer_fee = calculate_transfer_fee(transfer_fee, amount)?;
+        self.build_quote(amount, output_amount, fee, base_transfer_fee, with_referrer, price_before)
+    }
+
+    fn build_quote(
+        &self,
+        amount_in: u64,
+        amount_out: u64,
+        fee: u64,
+        base_transfer_fee: u64,
+        with_referrer: bool,
+        price_before: u128,
+    ) -> Result<TradeQuote> {
+        let price_after = self.spot_price()?;
+        let price_impact_bps = if price_before == 0 {
+            0
+        } else {
+            to_u64(price_after.abs_diff(price_before).checked_mul(BPS_DIV).or_overflow()?.checked_div(price_before).or_overflow()?)?
+        };
+        Ok(TradeQuote {
+            amount_in,
+            amount_out,
+            fee,
+            referrer_fee: if with_referrer { split_referral_fee(fee).1 } else { 0 },
+            base_transfer_fee,
+            price_impact_bps,
+            base_reserves: self.bookkept_base_reserves()?,
+            quote_reserves: self.total_quote_reserves()?,
+        })
+    }
+
+    // 0 disables the ceiling, the pool override wins over the global value
+    pub fn check_price_impact(&self, quote: &TradeQuote, default_max_price_impact_bps: u64) -> Result<()> {
+        let max_price_impact_bps = self.max_price_impact_bps.unwrap_or(default_max_price_impact_bps);
+        require!(
+            max_price_impact_bps == 0 || quote.price_impact_bps <= max_price_impact_bps,
+            PumpFunError::PriceImpactTooHigh
+        );
+        Ok(())
+    }
+
+    // rejects trades during a cooldown and starts a new reference window when the last one ran out
+    pub fn guard_circuit_breaker(&mut self, main_state: &MainState, slot: u64, is_buy: bool) -> Result<()> {
+        if slot < self.breaker_cooldown_until_slot {
+            require!(
+                is_buy.eq(&false) && main_state.circuit_breaker_mode == CircuitBreakerMode::SellOnly,
+                PumpFunError::CircuitBreakerActive
+            );
+        }
+        let window_end = self.breaker_window_start_slot.saturating_add(main_state.circuit_breaker_window_slots);
+        if self.breaker_reference_price == 0 || slot >= window_end {
+            self.breaker_reference_price = self.spot_price()?;
+            self.breaker_window_start_slot = slot;
+        }
+        Ok(())
+    }
+
+    // trips the breaker when the price moved more than the threshold inside the window,
+    // returns the reference price it was measured against and the movement
+    pub fn update_circuit_breaker(&mut self, main_state: &MainState, slot: u64) -> Result<Option<(u128, u64)>> {
+        if main_state.circuit_breaker_threshold_bps == 0 || self.breaker_reference_price == 0 {
+            return Ok(None);
+        }
+        let price = self.spot_price()?;
+        let movement_bps = to_u64(
+            price.abs_diff(self.breaker_reference_price).checked_mul(BPS_DIV).or_overflow()? / self.breaker_reference_price,
+        )?;
+        if movement_bps <= main_state.circuit_breaker_threshold_bps {
+            return Ok(None);
+        }
+        let reference_price = self.breaker_reference_price;
+        self.breaker_cooldown_until_slot = slot.saturating_add(main_state.circuit_breaker_cooldown_slots);
+        self.breaker_reference_price = price;
+        self.breaker_window_start_slot = self.breaker_cooldown_until_slot;
+        Ok(Some((reference_price, movement_bps)))
+    }
+
+    // (base tokens leaving the reserve, base tokens the buyer receives after the transfer fee)
+    pub fn compute_receivable_amount_on_buy(&mut self, quote_amount: u64, transfer_fee: Option<&TransferFee>) -> Result<(u64, u64)> {
+        require!(quote_amount > 0, PumpFunError::ZeroOutput);
+        let base_amount = match self.curve {
+            CurveKind::ConstantProduct => {
+                calculate_output_amount(quote_amount, self.total_quote_reserves()?, self.real_base_reserves)?
+            }
+            CurveKind::DualVirtualProduct => {
+                calculate_output_amount(quote_amount, self.total_quote_reserves()?, self.bookkept_base_reserves()?)?
+            }
+            curve => curve.quote_buy(self.sold_base_amount()?, self.init_real_base_reserves, quote_amount)?,
+        };
+        require!(base_amount > 0, PumpFunError::ZeroOutput);
+        self.real_base_reserves = withdraw_reserve(self.real_base_reserves, base_amount)?;
+        self.real_quote_reserves = checked_add(self.real_quote_reserves, quote_amount)?;
+        self.check_curve_invariant()?;
+        let received_amount = checked_sub(base_amount, calculate_transfer_fee(transfer_fee, base_amount)?)?;
+        require!(received_amount > 0, PumpFunError::ZeroOutput);
+        Ok((base_amount, received_amount))
+    }
+
+    // only what reaches the reserve after the transfer fee is sold into the curve
+    pub fn compute_receivable_amount_on_sell(&mut self, base_amount: u64, transfer_fee: Option<&TransferFee>) -> Result<u64> {
+        let base_amount = checked_sub(base_amount, calculate_transfer_fee(transfer_fee, base_amount)?)?;
+        require!(base_amount > 0, PumpFunError::ZeroOutput);
+        let quote_amount = match self.curve {
+            CurveKind::ConstantProduct => {
+                calculate_output_amount(base_amount, self.real_base_reserves, self.total_quote_reserves()?)?
+            }
+            CurveKind::DualVirtualProduct => {
+                calculate_output_amount(base_amount, self.bookkept_base_reserves()?, self.total_quote_reserves()?)?
+            }
+            curve => curve.quote_sell(self.sold_base_amount()?, base_amount)?,
+        };
+        require!(quote_amount > 0, PumpFunError::ZeroOutput);
+        self.real_base_reserves = checked_add(self.real_base_reserves, base_amount)?;
+        self.real_quote_reserves = withdraw_reserve(self.real_quote_reserves, quote_amount)?;
+        self.check_curve_invariant()?;
+        Ok(quote_amount)
+    }
+
+    pub fn compute_konst(&self) -> Result<u128> {
+        let base_reserves = match self.curve {
+            CurveKind::DualVirtualProduct => self.bookkept_base_reserves()?,
+            _ => self.real_base_reserves,
+        };
+        (base_reserves as u128)
+            .checked_mul(self.total_quote_reserves()? as u128)
+            .or_overflow()
+    }
+
+    // the curve must always be able to buy back every token it sold
+    pub fn check_curve_invariant(&self) -> Result<()> {
+        require!(self.real_base_reserves <= self.init_real_base_reserves, PumpFunError::CurveInvariantViolated);
+        match self.curve {
+            CurveKind::ConstantProduct | CurveKind::DualVirtualProduct => {
+                require!(self.compute_konst()? >= self.konst, PumpFunError::CurveInvariantViolated);
+            }
+            curve => {
+                let sold = self.sold_base_amount()?;
+                let buyback = curve.quote_sell(sold, sold)?;
+                require!(self.real_quote_reserves >= buyback, PumpFunError::CurveInvariantViolated);
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::{
+    math::checked_add,
+    PoolState,
+};
+
+impl PoolState {
+    // called once the reserves have moved, `quote_volume` is the gross quote amount of the trade
+    pub fn record_trade(&mut self, quote_volume: u64, fee: u64, is_buy: bool, new_trader: bool) -> Result<()> {
+        if is_buy {
+            self.total_buy_volume = checked_add(self.total_buy_volume, quote_volume)?;
+        } else {
+            self.total_sell_volume = checked_add(self.total_sell_volume, quote_volume)?;
+        }
+        self.trade_count = checked_add(self.trade_count, 1)?;
+        self.total_fees = checked_add(self.total_fees, fee)?;
+        if new_trader {
+            self.unique_traders = checked_add(self.unique_traders, 1)?;
+        }
+        // a drained curve has no spot price left
+        if self.real_base_reserves > 0 {
+            self.ath_price = self.ath_price.max(self.spot_price()?);
+        }
+        Ok(())
+    }
+}
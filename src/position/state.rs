@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::{
+    math::{checked_add, checked_sub, mul_div_ceil, to_u64, OrOverflow},
+    PRICE_PRECISION,
+};
+
+// average cost accounting of one wallet on one pool, quote amounts include fees
+#[account]
+pub struct UserPosition {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub tokens_bought: u64,
+    pub tokens_sold: u64,
+    pub quote_spent: u64,
+    pub quote_received: u64,
+    // tokens bought through the curve and not sold yet, and what they cost
+    pub base_amount: u64,
+    pub cost_basis: u64,
+    pub realized_pnl: i64,
+}
+
+impl UserPosition {
+    pub const MAX_SIZE: usize = std::mem::size_of::<Self>();
+    pub const PREFIX_SEED: &'static [u8] = b"position";
+
+    // false until the first trade of `owner` on `pool` has been recorded
+    pub fn is_initialized(&self) -> bool {
+        self.owner != Pubkey::default()
+    }
+
+    pub fn record_buy(&mut self, base_amount: u64, quote_amount: u64) -> Result<()> {
+        self.tokens_bought = checked_add(self.tokens_bought, base_amount)?;
+        self.quote_spent = checked_add(self.quote_spent, quote_amount)?;
+        self.base_amount = checked_add(self.base_amount, base_amount)?;
+        self.cost_basis = checked_add(self.cost_basis, quote_amount)?;
+        Ok(())
+    }
+
+    // tokens received outside of the curve have no cost, selling them is all profit
+    pub fn record_sell(&mut self, base_amount: u64, quote_amount: u64) -> Result<()> {
+        self.tokens_sold = checked_add(self.tokens_sold, base_amount)?;
+        self.quote_received = checked_add(self.quote_received, quote_amount)?;
+        let closed_amount = base_amount.min(self.base_amount);
+        let closed_cost = match self.base_amount {
+            0 => 0,
+            held => mul_div_ceil(self.cost_basis, closed_amount, held as u128)?,
+        };
+        self.base_amount = checked_sub(self.base_amount, closed_amount)?;
+        self.cost_basis = checked_sub(self.cost_basis, closed_cost)?;
+        let pnl = quote_amount as i128 - closed_cost as i128;
+        self.realized_pnl = i64::try_from(self.realized_pnl as i128 + pnl).ok().or_overflow()?;
+        Ok(())
+    }
+
+    // scaled by PRICE_PRECISION, 0 when nothing is held
+    pub fn average_entry_price(&self) -> Result<u64> {
+        match self.base_amount {
+            0 => Ok(0),
+            held => to_u64((self.cost_basis as u128).checked_mul(PRICE_PRECISION).or_overflow()? / held as u128),
+        }
+    }
+}
@@ -1,28 +1,308 @@
 use crate::{
-    constants::{FEE_PER_DIV, NATIVE_MINT_STR},
+    constants::NATIVE_MINT_STR,
     error::PumpFunError,
+    math::{checked_add, checked_sub},
+    MainState, PoolState,
+};
+use anchor_lang::{prelude::*, solana_program::program::invoke, system_program};
+use anchor_spl::{
+    token::{self, CloseAccount, SyncNative, TokenAccount, Transfer},
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+            transfer_hook::TransferHook,
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+    },
+    token_interface::{self, TransferChecked},
 };
-use anchor_lang::{prelude::*, solana_program::program::invoke};
-use anchor_spl::token::{self, CloseAccount, SyncNative, TokenAccount};
 
-pub fn check_balance_on_pool_creator(ata: &TokenAccount, require_amount: u64) -> bool {
-    if (ata.mint.to_string() == NATIVE_MINT_STR) {
+pub fn check_balance_on_pool_creator(mint: &Pubkey, balance: u64, require_amount: u64) -> bool {
+    if (mint.to_string() == NATIVE_MINT_STR) {
         return true;
     }
-    if ata.amount < require_amount {
+    if balance < require_amount {
         return false;
     }
     true
 }
 
-pub fn calculate_trading_fee(fee: u64, amount: u64) -> u64 {
-    (amount as u128)
-        .checked_mul(fee.into())
-        .unwrap()
-        .checked_div(FEE_PER_DIV)
-        .unwrap()
-        .checked_div(100)
-        .unwrap() as u64
+// transfer hooks and permanent delegates let a third party block or drain the reserves
+pub fn check_base_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    require!(state.get_extension::<TransferHook>().is_err(), PumpFunError::UnsupportedMintExtension);
+    require!(state.get_extension::<PermanentDelegate>().is_err(), PumpFunError::UnsupportedMintExtension);
+    Ok(())
+}
+
+// fee the base mint withholds on transfers this epoch, None without the extension
+pub fn get_transfer_fee(mint: &AccountInfo) -> Result<Option<TransferFee>> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(Some(*config.get_epoch_fee(Clock::get()?.epoch))),
+        Err(_) => Ok(None),
+    }
+}
+
+// token-2022 refuses to close a token account that still holds withheld transfer fees
+pub fn has_withheld_transfer_fees(account: &AccountInfo) -> Result<bool> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    match state.get_extension::<TransferFeeAmount>() {
+        Ok(fee_amount) => Ok(u64::from(fee_amount.withheld_amount) > 0),
+        Err(_) => Ok(false),
+    }
+}
+
+// base side transfers go through transfer_checked so token-2022 mints work too
+pub fn transfer_base<'a>(
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    decimals: u8,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from,
+        mint,
+        to,
+        authority,
+    };
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds),
+        amount,
+        decimals,
+    )
+}
+
+// wrapped SOL pools move lamports through the system program, other quote mints move as spl tokens
+pub fn transfer_quote<'a>(
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    program: AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    if program.key() == system_program::ID {
+        let cpi_accounts = system_program::Transfer { from, to };
+        return system_program::transfer(CpiContext::new_with_signer(program, cpi_accounts, signer_seeds), amount);
+    }
+    let cpi_accounts = Transfer { from, to, authority };
+    token::transfer(CpiContext::new_with_signer(program, cpi_accounts, signer_seeds), amount)
+}
+
+// the vault keeps its rent exempt minimum, only the lamports above it are reserves
+pub fn sol_vault_balance(sol_vault: &AccountInfo) -> Result<u64> {
+    Ok(sol_vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0)))
+}
+
+// graduation still hands out wrapped SOL, so the vault lamports go back into the reserve ata first
+pub fn wrap_sol_vault<'a>(
+    sol_vault: AccountInfo<'a>,
+    reserver_quote_ata: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    transfer_quote(sol_vault.clone(), reserver_quote_ata.clone(), sol_vault, system_program, amount, &[signer_seeds])?;
+    let sync_accounts = SyncNative {
+        account: reserver_quote_ata,
+    };
+    token::sync_native(CpiContext::new(token_program, sync_accounts))
+}
+
+// endpoints of the quote leg of a trade: wallets and the sol vault for wrapped SOL pools, token accounts otherwise
+pub struct QuoteAccounts<'a> {
+    pub user: AccountInfo<'a>,
+    pub fee: AccountInfo<'a>,
+    pub referrer: AccountInfo<'a>,
+    pub reserve: AccountInfo<'a>,
+    pub program: AccountInfo<'a>,
+}
+
+// a lamport transfer can't leave a wallet below its rent exempt minimum
+fn can_receive_lamports(to: &AccountInfo, amount: u64) -> Result<bool> {
+    Ok(to.lamports().saturating_add(amount) >= Rent::get()?.minimum_balance(to.data_len()))
+}
+
+// pays the protocol and referrer shares of a trading fee out of `payer`. on a wrapped SOL pool a referrer share
+// that can't land goes to the fee recipient, and what the fee recipient can't take stays in the reserve for skim
+pub fn pay_trading_fee<'a>(
+    quote_accounts: &QuoteAccounts<'a>,
+    payer: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    protocol_fee: u64,
+    referrer_fee: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let program = quote_accounts.program.clone();
+    if program.key() != system_program::ID {
+        transfer_quote(payer.clone(), quote_accounts.fee.clone(), authority.clone(), program.clone(), protocol_fee, signer_seeds)?;
+        return transfer_quote(payer, quote_accounts.referrer.clone(), authority, program, referrer_fee, signer_seeds);
+    }
+
+    let mut fee_share = protocol_fee;
+    if can_receive_lamports(&quote_accounts.referrer, referrer_fee)? {
+        transfer_quote(payer.clone(), quote_accounts.referrer.clone(), authority.clone(), program.clone(), referrer_fee, signer_seeds)?;
+    } else {
+        fee_share = checked_add(fee_share, referrer_fee)?;
+    }
+    let destination = if can_receive_lamports(&quote_accounts.fee, fee_share)? {
+        quote_accounts.fee.clone()
+    } else {
+        quote_accounts.reserve.clone()
+    };
+    if destination.key() == payer.key() {
+        return Ok(());
+    }
+    transfer_quote(payer, destination, authority, program, fee_share, signer_seeds)
+}
+
+pub fn required_quote_ata<'a>(ata: &Option<Box<Account<'a, TokenAccount>>>) -> Result<AccountInfo<'a>> {
+    ata.as_ref()
+        .map(|ata| ata.to_account_info())
+        .ok_or_else(|| PumpFunError::MissingQuoteAccount.into())
+}
+
+fn pay_graduation_fees<'a>(
+    migration_fee: u64,
+    creator_reward: u64,
+    pool: AccountInfo<'a>,
+    reserver_quote_ata: AccountInfo<'a>,
+    fee_quote_ata: AccountInfo<'a>,
+    creator_quote_ata: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    if migration_fee > 0 {
+        let fee_transfer_cpi_account = Transfer {
+            from: reserver_quote_ata.clone(),
+            to: fee_quote_ata,
+            authority: pool.clone(),
+        };
+        token::transfer(CpiContext::new_with_signer(token_program.clone(), fee_transfer_cpi_account, &[signer_seeds]), migration_fee)?;
+    }
+    if creator_reward > 0 {
+        let reward_transfer_cpi_account = Transfer {
+            from: reserver_quote_ata,
+            to: creator_quote_ata,
+            authority: pool,
+        };
+        token::transfer(CpiContext::new_with_signer(token_program, reward_transfer_cpi_account, &[signer_seeds]), creator_reward)?;
+    }
+    Ok(())
+}
+
+// accounts a completed curve needs to hand its reserves over to an amm or a dex
+pub struct GraduationAccounts<'a> {
+    pub pool: AccountInfo<'a>,
+    pub sol_vault: AccountInfo<'a>,
+    pub base_mint: AccountInfo<'a>,
+    pub reserver_base_ata: AccountInfo<'a>,
+    pub reserver_quote_ata: AccountInfo<'a>,
+    pub fee_quote_ata: AccountInfo<'a>,
+    pub creator_quote_ata: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+    pub base_token_program: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+}
+
+// what is left in the reserve atas for the migration target once the curve is closed
+pub struct Graduation {
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub migration_fee: u64,
+    pub creator_reward: u64,
+}
+
+// wraps the sol vault, pays the graduation fees, burns the base tokens not needed at the closing price
+// and closes the curve. the caller moves `base_amount` and `quote_amount` to the migration target
+pub fn graduate<'a>(
+    accounts: GraduationAccounts<'a>,
+    pool_state: &mut PoolState,
+    main_state: &MainState,
+    pool_bump: u8,
+    sol_vault_bump: u8,
+) -> Result<Graduation> {
+    require!(pool_state.complete.eq(&true), PumpFunError::BondingCurveIncomplete);
+    require!(pool_state.migrated.eq(&false), PumpFunError::AlreadyMigrated);
+
+    let (migration_fee, creator_reward) = pool_state.graduation_fees(main_state)?;
+    let quote_amount = checked_sub(checked_sub(pool_state.real_quote_reserves, migration_fee)?, creator_reward)?;
+    let base_amount = pool_state.graduation_base_amount(quote_amount)?;
+    let burn_amount = checked_sub(pool_state.bookkept_base_reserves()?, base_amount)?;
+
+    let pool_seeds: &[&[u8]] = &[
+        PoolState::PREFIX_SEED,
+        pool_state.base_mint.as_ref(),
+        pool_state.quote_mint.as_ref(),
+        &[pool_bump],
+    ];
+
+    // a wrapped SOL pool keeps its reserve in the vault until graduation
+    if pool_state.is_native_quote() {
+        let pool_key = accounts.pool.key();
+        wrap_sol_vault(
+            accounts.sol_vault,
+            accounts.reserver_quote_ata.clone(),
+            accounts.system_program,
+            accounts.token_program.clone(),
+            pool_state.real_quote_reserves,
+            &[PoolState::SOL_VAULT_SEED, pool_key.as_ref(), &[sol_vault_bump]],
+        )?;
+    }
+
+    pay_graduation_fees(
+        migration_fee,
+        creator_reward,
+        accounts.pool.clone(),
+        accounts.reserver_quote_ata,
+        accounts.fee_quote_ata,
+        accounts.creator_quote_ata,
+        accounts.token_program,
+        pool_seeds,
+    )?;
+
+    // burning every token that does not go to the migration target, so it opens at the closing price
+    if burn_amount > 0 {
+        let burn_cpi_account = token_interface::Burn {
+            mint: accounts.base_mint,
+            from: accounts.reserver_base_ata,
+            authority: accounts.pool,
+        };
+        token_interface::burn(
+            CpiContext::new_with_signer(accounts.base_token_program, burn_cpi_account, &[pool_seeds]),
+            burn_amount,
+        )?;
+    }
+
+    pool_state.real_base_reserves = 0;
+    pool_state.virt_base_reserves = 0;
+    pool_state.real_quote_reserves = 0;
+    pool_state.migrated = true;
+
+    Ok(Graduation { base_amount, quote_amount, migration_fee, creator_reward })
 }
 
 pub fn close_token_account<'a>(
@@ -52,7 +332,7 @@ pub fn sync_native_amount<'a>(
         sync_amount = require_amount - ata_balance
     }
     if sync_amount != 0 {
-        if (owner.lamports.borrow().clone() < require_amount) {
+        if (owner.lamports() < require_amount) {
             return Err(PumpFunError::InsufficientFund.into());
         }
         let sol_transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
@@ -0,0 +1,339 @@
+#![allow(dead_code)]
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use pump_fun::{
+    constants::TOTAL_SUPPLY,
+    main_state::{MainState, UpdateMainStateInput},
+    pool::{CreatePoolInput, CurveKind, PoolState, TradeHistory},
+    position::UserPosition,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account};
+use spl_token_2022::extension::StateWithExtensions;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const INCINERATOR: Pubkey = solana_sdk::incinerator::ID;
+
+// pump_fun runs as a native processor, so `cargo test` needs no sbf build
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("pump_fun", pump_fun::ID, processor!(process_pump_fun));
+    add_native_mint(&mut program_test);
+    program_test
+}
+
+// the anchor entrypoint ties the account slice to the account lifetime, which the test processor signature doesn't
+fn process_pump_fun(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    pump_fun::entry(program_id, accounts, data)
+}
+
+fn add_native_mint(program_test: &mut ProgramTest) {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        spl_token::native_mint::ID,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+pub async fn process(context: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+    let mut all_ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+    all_ixs.extend_from_slice(ixs);
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let tx = Transaction::new_signed_with_payer(&all_ixs, Some(&context.payer.pubkey()), &all_signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+pub fn custom_error(result: Result<(), BanksClientError>) -> Option<u32> {
+    match result.err()?.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
+pub async fn get_account(context: &mut ProgramTestContext, key: Pubkey) -> Account {
+    context.banks_client.get_account(key).await.unwrap().expect("account not found")
+}
+
+pub async fn get_anchor_account<T: AccountDeserialize>(context: &mut ProgramTestContext, key: Pubkey) -> T {
+    let account = get_account(context, key).await;
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn token_amount(context: &mut ProgramTestContext, key: Pubkey) -> u64 {
+    let account = get_account(context, key).await;
+    // token-2022 accounts carry extensions past the classic layout
+    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap().base.amount
+}
+
+pub async fn mint_supply(context: &mut ProgramTestContext, key: Pubkey) -> u64 {
+    let account = get_account(context, key).await;
+    StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap().base.supply
+}
+
+pub async fn fund(context: &mut ProgramTestContext, to: &Pubkey, lamports: u64) {
+    let ix = system_instruction::transfer(&context.payer.pubkey(), to, lamports);
+    process(context, &[ix], &[]).await.unwrap();
+}
+
+pub fn main_state_key() -> Pubkey {
+    Pubkey::find_program_address(&[MainState::PREFIX_SEED], &pump_fun::ID).0
+}
+
+pub fn pool_state_key(base_mint: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PoolState::PREFIX_SEED, base_mint.as_ref(), quote_mint.as_ref()], &pump_fun::ID).0
+}
+
+pub fn sol_vault_key(pool_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PoolState::SOL_VAULT_SEED, pool_state.as_ref()], &pump_fun::ID).0
+}
+
+pub fn trade_history_key(pool_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TradeHistory::PREFIX_SEED, pool_state.as_ref()], &pump_fun::ID).0
+}
+
+pub fn user_position_key(pool_state: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[UserPosition::PREFIX_SEED, pool_state.as_ref(), owner.as_ref()], &pump_fun::ID).0
+}
+
+pub async fn init_main_state(context: &mut ProgramTestContext) {
+    let ix = Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::AInitMainState {
+            owner: context.payer.pubkey(),
+            main_state: main_state_key(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::InitMainState {}.data(),
+    };
+    process(context, &[ix], &[]).await.unwrap();
+}
+
+// rewrites the owner settings and applies `update` on top
+pub async fn update_main_state(context: &mut ProgramTestContext, update: impl FnOnce(&mut UpdateMainStateInput)) {
+    let ix = update_main_state_ix(context, update).await;
+    process(context, &[ix], &[]).await.unwrap();
+}
+
+// the owner's update_main_state keeping every field `update` leaves alone
+pub async fn update_main_state_ix(context: &mut ProgramTestContext, update: impl FnOnce(&mut UpdateMainStateInput)) -> Instruction {
+    let main_state: MainState = get_anchor_account(context, main_state_key()).await;
+    let mut input = UpdateMainStateInput {
+        owner: main_state.owner,
+        fee_recipient: main_state.fee_recipient,
+        trading_fee: main_state.trading_fee,
+        max_buy_limit: main_state.max_buy_limit,
+        dex_allowlist: None,
+        migration_fee: None,
+        migration_fee_bps: None,
+        creator_reward: None,
+        allowed_curves: None,
+        init_virt_quote_reserves: None,
+        max_price_impact_bps: None,
+        circuit_breaker_threshold_bps: None,
+        circuit_breaker_window_slots: None,
+        circuit_breaker_cooldown_slots: None,
+        circuit_breaker_mode: None,
+    };
+    update(&mut input);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::AUpdateMainState {
+            owner: context.payer.pubkey(),
+            main_state: main_state_key(),
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::UpdateMainState { input }.data(),
+    }
+}
+
+// classic spl mint with the whole supply in the creator ata and both authorities revoked
+pub async fn create_base_mint(context: &mut ProgramTestContext, creator: &Keypair) -> Pubkey {
+    create_base_mint_with_supply(context, creator, TOTAL_SUPPLY).await
+}
+
+pub async fn create_base_mint_with_supply(context: &mut ProgramTestContext, creator: &Keypair, supply: u64) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let ixs = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &creator.pubkey(), None, 6).unwrap(),
+        create_associated_token_account(&payer, &creator.pubkey(), &mint.pubkey(), &spl_token::ID),
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &get_associated_token_address(&creator.pubkey(), &mint.pubkey()),
+            &creator.pubkey(),
+            &[],
+            supply,
+        )
+        .unwrap(),
+        spl_token::instruction::set_authority(
+            &spl_token::ID,
+            &mint.pubkey(),
+            None,
+            spl_token::instruction::AuthorityType::MintTokens,
+            &creator.pubkey(),
+            &[],
+        )
+        .unwrap(),
+    ];
+    process(context, &ixs, &[&mint, creator]).await.unwrap();
+    mint.pubkey()
+}
+
+// wrapped SOL pool over the whole supply of `base_mint`
+pub async fn create_sol_pool(context: &mut ProgramTestContext, creator: &Keypair, base_mint: Pubkey) -> Pubkey {
+    let ix = create_sol_pool_ix(&creator.pubkey(), base_mint, TOTAL_SUPPLY);
+    process(context, &[ix], &[creator]).await.unwrap();
+    pool_state_key(&base_mint, &spl_token::native_mint::ID)
+}
+
+pub fn create_sol_pool_ix(creator: &Pubkey, base_mint: Pubkey, base_amount: u64) -> Instruction {
+    let quote_mint = spl_token::native_mint::ID;
+    let pool_state = pool_state_key(&base_mint, &quote_mint);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::ACreatePool {
+            creator: *creator,
+            main_state: main_state_key(),
+            pool_state,
+            trade_history: trade_history_key(&pool_state),
+            sol_vault: sol_vault_key(&pool_state),
+            base_mint,
+            quote_mint,
+            quote_config: None,
+            creator_base_ata: get_associated_token_address(creator, &base_mint),
+            creator_quote_ata: None,
+            reserver_base_ata: get_associated_token_address(&pool_state, &base_mint),
+            reserver_quote_ata: None,
+            associated_token_program: spl_associated_token_account::ID,
+            token_program: spl_token::ID,
+            base_token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::CreatePool {
+            input: CreatePoolInput {
+                base_amount,
+                quote_amount: 0,
+                curve: CurveKind::ConstantProduct,
+            },
+        }
+        .data(),
+    }
+}
+
+pub fn buy_ix(buyer: &Pubkey, fee_recipient: &Pubkey, referrer: &Pubkey, base_mint: Pubkey, amount: u64) -> Instruction {
+    let quote_mint = spl_token::native_mint::ID;
+    let pool_state = pool_state_key(&base_mint, &quote_mint);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::ABuy {
+            buyer: *buyer,
+            main_state: main_state_key(),
+            fee_recipient: *fee_recipient,
+            fee_referrer: *referrer,
+            fee_referrer_ata: None,
+            fee_quote_ata: None,
+            pool_state,
+            trade_history: trade_history_key(&pool_state),
+            sol_vault: sol_vault_key(&pool_state),
+            user_position: user_position_key(&pool_state, buyer),
+            base_mint,
+            quote_mint,
+            buyer_base_ata: get_associated_token_address(buyer, &base_mint),
+            buyer_quote_ata: None,
+            reserver_base_ata: get_associated_token_address(&pool_state, &base_mint),
+            reserver_quote_ata: None,
+            associated_token_program: spl_associated_token_account::ID,
+            token_program: spl_token::ID,
+            base_token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::Buy { amount }.data(),
+    }
+}
+
+pub fn sell_ix(seller: &Pubkey, fee_recipient: &Pubkey, referrer: &Pubkey, base_mint: Pubkey, amount: u64) -> Instruction {
+    let mut ix = sell_with_min_out_ix(seller, fee_recipient, referrer, base_mint, amount, 0);
+    ix.data = pump_fun::instruction::Sell { amount }.data();
+    ix
+}
+
+pub fn sell_with_min_out_ix(
+    seller: &Pubkey,
+    fee_recipient: &Pubkey,
+    referrer: &Pubkey,
+    base_mint: Pubkey,
+    amount: u64,
+    min_quote_out: u64,
+) -> Instruction {
+    let quote_mint = spl_token::native_mint::ID;
+    let pool_state = pool_state_key(&base_mint, &quote_mint);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::ASell {
+            seller: *seller,
+            main_state: main_state_key(),
+            fee_recipient: *fee_recipient,
+            fee_quote_ata: None,
+            fee_referrer: *referrer,
+            fee_referrer_ata: None,
+            pool_state,
+            trade_history: trade_history_key(&pool_state),
+            sol_vault: sol_vault_key(&pool_state),
+            user_position: user_position_key(&pool_state, seller),
+            base_mint,
+            quote_mint,
+            seller_base_ata: get_associated_token_address(seller, &base_mint),
+            seller_quote_ata: None,
+            reserver_base_ata: get_associated_token_address(&pool_state, &base_mint),
+            reserver_quote_ata: None,
+            associated_token_program: spl_associated_token_account::ID,
+            token_program: spl_token::ID,
+            base_token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::SellWithMinOut { amount, min_quote_out }.data(),
+    }
+}
@@ -4,7 +4,14 @@ import { Logger, ILogObj } from 'tslog'
 
 export const Seeds = {
     main: Buffer.from('main'),
-    pool: Buffer.from('pool')
+    pool: Buffer.from('pool'),
+    solVault: Buffer.from('sol_vault'),
+    history: Buffer.from('history'),
+    position: Buffer.from('position'),
+    quoteConfig: Buffer.from('quote_config'),
+    amm: Buffer.from('amm'),
+    lpMint: Buffer.from('lp_mint'),
+    metadata: Buffer.from('metadata'),
 }
 
 export const FEE_PRE_DIV = 1000
@@ -12,6 +19,7 @@ export const PROGRAMS = {
     systemProgram: web3.SystemProgram.programId,
     tokenProgram: TOKEN_PROGRAM_ID,
     associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+    tokenMetadataProgram: new web3.PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s'),
 }
 
 export const log: Logger<ILogObj> = new Logger();
@@ -2,20 +2,29 @@ import { AnchorProvider, Program, Wallet, web3 } from '@coral-xyz/anchor'
 import { PumpFun, IDL as PumpFunIDL } from '../../target/types/pump_fun'
 import { Result, TxPassResult } from './types'
 import { PumpFunError } from './error';
-import { FEE_PRE_DIV, PROGRAMS, debug } from './constants';
+import { FEE_PRE_DIV, PROGRAMS, Seeds, debug } from './constants';
 import { Pdas } from './pdas';
 import BN from 'bn.js';
 import { calculateOutputAmount, getMultipleAccountsInfo, getPubkeyFromStr, sleep } from './utils';
-import { MintLayout, NATIVE_MINT, getAssociatedTokenAddressSync, getMint, mintTo } from '@solana/spl-token';
+import { MintLayout, NATIVE_MINT, TOKEN_2022_PROGRAM_ID, getAssociatedTokenAddressSync, getMint, mintTo } from '@solana/spl-token';
 import { calcDecimalValue, calcNonDecimalValue } from './base/utils';
 import { toBufferBE, toBigIntBE } from 'bigint-buffer'
 import { PoolStateLayout } from './base/types';
 import { utf8 } from '@coral-xyz/anchor/dist/cjs/utils/bytes';
 import { LAMPORTS_PER_SOL } from '@solana/web3.js';
 
-const { systemProgram, tokenProgram, associatedTokenProgram } = PROGRAMS
+const { systemProgram, tokenProgram, associatedTokenProgram, tokenMetadataProgram } = PROGRAMS
+// the program treats the incinerator as "no referrer"
+const NO_REFERRER = new web3.PublicKey("1nc1nerator11111111111111111111111111111111")
 const todo = null as any;
 
+export type CurveKind =
+    | { constantProduct: {} }
+    | { dualVirtualProduct: {} }
+    | { linear: { startPrice: BN, stepSize: BN, increment: BN } }
+    | { exponential: { startPrice: BN, stepSize: BN, growthBps: BN } }
+    | { stepped: { stepSize: BN, prices: BN[] } }
+
 export type MainStateInfo = {
     tradingFee: number,
     owner: string, 
@@ -32,6 +41,13 @@ export type PoolInfo = {
     virtQuoteReserves: BN,
 }
 
+export type UserPositionInfo = {
+    tokensBought: BN,
+    tokensSold: BN,
+    quoteSpent: BN,
+    quoteReceived: BN,
+}
+
 export class Connectivity {
     private program: Program<PumpFun>
     private connection: web3.Connection
@@ -64,16 +80,17 @@ export class Connectivity {
         return { Ok: { txSignature } }
     }
 
-    async updateMainState(input: { newOwner?: string, newFeeRecipient?: string, newTotalTokenSupply?: number, newInitRealBaseReserves?: number, newInitVirtBaseReserves?: number, newInitVirtQuoteReserves?: number, tradingFee?: number }): Promise<Result<TxPassResult>> {
+    async updateMainState(input: { newOwner?: string, newFeeRecipient?: string, tradingFee?: number, maxBuyLimit?: number, migrationFee?: number, migrationFeeBps?: number, creatorReward?: number, allowedCurves?: number, initVirtQuoteReserves?: number, maxPriceImpactBps?: number }): Promise<Result<TxPassResult>> {
         const owner = this.provider.publicKey
         if (!owner) return { Err: PumpFunError.WALLET_NOT_FOUND }
-        let newOwner: null | web3.PublicKey = null
-        let newFeeRecipient: null | web3.PublicKey = null
-        let newTotalTokenSupply: null | BN = null
-        let newInitRealBaseReserves: null | BN = null
-        let newInitVirtBaseReserves: null | BN = null
-        let newInitVirtQuoteReserves: null | BN = null
-        let tradingFee: null | BN = null
+        const mainStateInfo = await this.program.account.mainState.fetch(this.pdas.mainState)
+            .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
+        if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
+        // owner, fee recipient, trading fee and buy limit are always rewritten, the rest is optional on chain
+        let newOwner = mainStateInfo.owner
+        let newFeeRecipient = mainStateInfo.feeRecipient
+        let tradingFee = mainStateInfo.tradingFee
+        let maxBuyLimit = mainStateInfo.maxBuyLimit
 
         if (input.newOwner) {
             const address = getPubkeyFromStr(input.newOwner)
@@ -85,36 +102,32 @@ export class Connectivity {
             if (!address) return { Err: PumpFunError.INVALID_INPUT }
             newFeeRecipient = address
         }
-        if (input.newTotalTokenSupply) {
-            const tmpTotalTokenSupply = input.newTotalTokenSupply
-            newTotalTokenSupply = new BN(tmpTotalTokenSupply)
-        }
-        if (input.newInitRealBaseReserves) {
-            const tmpRealTokenReserves = input.newInitRealBaseReserves
-            newInitRealBaseReserves = new BN(tmpRealTokenReserves)
-        }
-        if (input.newInitVirtBaseReserves) {
-            const tmpVirtTokenReserves = input.newInitVirtBaseReserves
-            newInitVirtBaseReserves = new BN(tmpVirtTokenReserves)
-        }
-        if (input.newInitVirtQuoteReserves) {
-            const tmpVirtSolReserves = input.newInitVirtQuoteReserves
-            newInitVirtQuoteReserves = new BN(tmpVirtSolReserves)
-        }
         if (input.tradingFee) {
             const tmpFee = Math.trunc(input.tradingFee * FEE_PRE_DIV)
             tradingFee = new BN(tmpFee)
         }
-        
-        const txSignature = await this.program.methods.updateMainState({ 
-            owner: newOwner, 
-            feeRecipient: newFeeRecipient, 
-            tradingFee, 
-            totalTokenSupply: newTotalTokenSupply, 
-            initRealBaseReserves: newInitRealBaseReserves, 
-            initVirtBaseReserves: newInitVirtBaseReserves, 
-            initVirtQuoteReserves: newInitVirtQuoteReserves, 
-			})
+        if (input.maxBuyLimit) {
+            maxBuyLimit = new BN(calcNonDecimalValue(input.maxBuyLimit, 9).toString())
+        }
+        const optionalBN = (value?: number) => value === undefined ? null : new BN(value)
+
+        const txSignature = await this.program.methods.updateMainState({
+            owner: newOwner,
+            feeRecipient: newFeeRecipient,
+            tradingFee,
+            maxBuyLimit,
+            dexAllowlist: null,
+            migrationFee: optionalBN(input.migrationFee),
+            migrationFeeBps: optionalBN(input.migrationFeeBps),
+            creatorReward: optionalBN(input.creatorReward),
+            allowedCurves: input.allowedCurves ?? null,
+            initVirtQuoteReserves: optionalBN(input.initVirtQuoteReserves),
+            maxPriceImpactBps: optionalBN(input.maxPriceImpactBps),
+            circuitBreakerThresholdBps: null,
+            circuitBreakerWindowSlots: null,
+            circuitBreakerCooldownSlots: null,
+            circuitBreakerMode: null,
+        })
         .accounts({
             owner, 
             mainState: this.pdas.mainState,
@@ -126,42 +139,62 @@ export class Connectivity {
         return { Ok: { txSignature } }
     }
 
-    async createPool(input: { baseToken: string, quoteToken: string, baseAmount: number, quoteAmount: number }): Promise<Result<TxPassResult & { poolId: string }>> {
+    // allowlists a non-SOL quote mint, amounts are raw quote units
+    async setQuoteConfig(input: { quoteToken: string, enabled: boolean, initVirtQuoteReserves: number, completeThreshold: number, maxBuyLimit: number, migrationFee?: number, creatorReward?: number }): Promise<Result<TxPassResult>> {
+        const owner = this.provider.publicKey
+        if (!owner) return { Err: PumpFunError.WALLET_NOT_FOUND }
+        const quoteMint = getPubkeyFromStr(input.quoteToken)
+        if (!quoteMint) return { Err: PumpFunError.INVALID_INPUT }
+        const txSignature = await this.program.methods.setQuoteConfig({
+            enabled: input.enabled,
+            initVirtQuoteReserves: new BN(input.initVirtQuoteReserves),
+            completeThreshold: new BN(input.completeThreshold),
+            maxBuyLimit: new BN(input.maxBuyLimit),
+            migrationFee: new BN(input.migrationFee ?? 0),
+            creatorReward: new BN(input.creatorReward ?? 0),
+        }).accounts({
+            owner, quoteMint,
+            mainState: this.pdas.mainState,
+            quoteConfig: this.pdas.getQuoteConfigAccount(quoteMint),
+            systemProgram,
+        }).rpc().catch(setQuoteConfigError => {
+            debug({ setQuoteConfigError })
+            return null
+        })
+        if (!txSignature) return { Err: PumpFunError.TX_FAILED }
+        return { Ok: { txSignature } }
+    }
+
+    async createPool(input: { baseToken: string, quoteToken: string, baseAmount: number, quoteAmount: number, curve?: CurveKind }): Promise<Result<TxPassResult & { poolId: string }>> {
         const creator = this.provider.publicKey
         if (!creator) return { Err: PumpFunError.WALLET_NOT_FOUND }
         const baseMint = getPubkeyFromStr(input.baseToken)
         const quoteMint = getPubkeyFromStr(input.quoteToken)
         if (!baseMint || !quoteMint) return { Err: PumpFunError.INVALID_INPUT }
-        // const infos = await getMultipleAccountsInfo(this.connection, [baseMint, quoteMint])
-        // if (!infos) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
-        // const [baseMintAccountInfo, quoteMintAccountInfo] = infos
-        // if (!baseMintAccountInfo) {
-        //     debug("base token not found")
-        //     return { Err: PumpFunError.TOKEN_NOT_FOUND }
-        // }
-        // if (!quoteMintAccountInfo) {
-        //     debug("quote token not found")
-        //     return { Err: PumpFunError.TOKEN_NOT_FOUND }
-        // }
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const isNativeQuote = quoteMint.equals(NATIVE_MINT)
         const baseMintDecimals = /* MintLayout.decode(baseMintAccountInfo.data).decimals */ 6
         const quoteMintDecimals = /* MintLayout.decode(quoteMintAccountInfo.data).decimals */ 9
         const baseAmount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.baseAmount, baseMintDecimals).toString()), 8))
         const quoteAmount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.quoteAmount, quoteMintDecimals).toString()), 8))
-        const creatorBaseAta = getAssociatedTokenAddressSync(baseMint, creator)
-        const creatorQuoteAta = getAssociatedTokenAddressSync(quoteMint, creator)
         const poolState = this.pdas.getPoolStateAccount({ baseMint, quoteMint, owner: creator })
-        const reserverBaseAta = getAssociatedTokenAddressSync(baseMint, poolState, true)
-        const reserverQuoteAta = getAssociatedTokenAddressSync(quoteMint, poolState, true)
-        const txSignature = await this.program.methods.createPool({ baseAmount, quoteAmount }).accounts({
+        const txSignature = await this.program.methods.createPool({ baseAmount, quoteAmount, curve: input.curve ?? { constantProduct: {} } }).accounts({
             creator: creator, baseMint, quoteMint,
             mainState: this.pdas.mainState,
-            creatorBaseAta, creatorQuoteAta,
             poolState,
+            tradeHistory: this.pdas.getTradeHistoryAccount(poolState),
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            // wrapped SOL pools keep their reserve in the vault and take no quote config
+            quoteConfig: isNativeQuote ? null : this.pdas.getQuoteConfigAccount(quoteMint),
+            creatorBaseAta: getAssociatedTokenAddressSync(baseMint, creator, false, baseTokenProgram),
+            creatorQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, creator),
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, poolState, true),
             systemProgram,
             associatedTokenProgram,
             tokenProgram,
-            reserverBaseAta,
-            reserverQuoteAta,
+            baseTokenProgram,
         }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })]).rpc().catch(createPoolError => {
             debug({ createPoolError })
             return null
@@ -170,11 +203,54 @@ export class Connectivity {
         return { Ok: { txSignature, poolId: poolState.toBase58() } }
     }
 
-    async buy(input: { amount: number, poolId: string }) {
+    // mints a fresh token straight into a new pool, `token2022` picks the token program of the new mint
+    async createTokenAndPool(input: { name: string, symbol: string, uri: string, quoteToken: string, decimals?: number, token2022?: boolean, curve?: CurveKind }): Promise<Result<TxPassResult & { poolId: string, baseMint: string }>> {
+        const creator = this.provider.publicKey
+        if (!creator) return { Err: PumpFunError.WALLET_NOT_FOUND }
+        const quoteMint = getPubkeyFromStr(input.quoteToken)
+        if (!quoteMint) return { Err: PumpFunError.INVALID_INPUT }
+        const isNativeQuote = quoteMint.equals(NATIVE_MINT)
+        const baseTokenProgram = input.token2022 ? TOKEN_2022_PROGRAM_ID : tokenProgram
+        const baseMintKeypair = web3.Keypair.generate()
+        const baseMint = baseMintKeypair.publicKey
+        const poolState = this.pdas.getPoolStateAccount({ baseMint, quoteMint, owner: creator })
+        const txSignature = await this.program.methods.createTokenAndPool({
+            decimals: input.decimals ?? 6,
+            name: input.name,
+            symbol: input.symbol,
+            uri: input.uri,
+            curve: input.curve ?? { constantProduct: {} },
+        }).accounts({
+            creator, baseMint, quoteMint,
+            mainState: this.pdas.mainState,
+            poolState,
+            tradeHistory: this.pdas.getTradeHistoryAccount(poolState),
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            quoteConfig: isNativeQuote ? null : this.pdas.getQuoteConfigAccount(quoteMint),
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, poolState, true),
+            metadata: this.pdas.getMetadataAccount(baseMint),
+            systemProgram,
+            associatedTokenProgram,
+            tokenProgram,
+            baseTokenProgram,
+            tokenMetadataProgram,
+            rent: web3.SYSVAR_RENT_PUBKEY,
+        }).signers([baseMintKeypair]).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })]).rpc().catch(createTokenAndPoolError => {
+            debug({ createTokenAndPoolError })
+            return null
+        })
+        if (!txSignature) return { Err: PumpFunError.TX_FAILED }
+        return { Ok: { txSignature, poolId: poolState.toBase58(), baseMint: baseMint.toBase58() } }
+    }
+
+    async buy(input: { amount: number, poolId: string, referrer?: string }) {
         const buyer = this.provider.publicKey
         if (!buyer) return { Err: PumpFunError.WALLET_NOT_FOUND }
         const poolState = getPubkeyFromStr(input.poolId)
         if (!poolState) return { Err: PumpFunError.INVALID_INPUT }
+        const feeReferrer = input.referrer ? getPubkeyFromStr(input.referrer) : NO_REFERRER
+        if (!feeReferrer) return { Err: PumpFunError.INVALID_INPUT }
         const mainStateInfo = await this.program.account.mainState.fetch(this.pdas.mainState)
             .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
         if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
@@ -182,29 +258,29 @@ export class Connectivity {
             .catch((fetchPoolInfoError) => { debug({ fetchPoolInfoError }); return null })
         if (!poolInfo) return { Err: PumpFunError.POOL_NOT_FOUND }
         const { baseMint, quoteMint } = poolInfo
-        // const reserveBaseAmount = toBigIntBE(poolInfo.reserveBase.toBuffer())
-        // const reserveQuoteAmount = toBigIntBE(poolInfo.reserveQuote.toBuffer())
-        // const accountInfoes = await getMultipleAccountsInfo(this.connection, [baseMint, quoteMint])
-        // if (!accountInfoes) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
-        // const [baseMintAccountInfo, quoteMintAccountInfo] = accountInfoes;
-        // if (!baseMintAccountInfo) return { Err: PumpFunError.TOKEN_NOT_FOUND }
-        // const baseMintDecimals = MintLayout.decode(baseMintAccountInfo.data).decimals
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const isNativeQuote = quoteMint.equals(NATIVE_MINT)
         const amount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.amount, 9).toString()), 8))
-        const buyerBaseAta = getAssociatedTokenAddressSync(baseMint, buyer)
-        const buyerQuoteAta = getAssociatedTokenAddressSync(quoteMint, buyer)
-        const feeQuoteAta = getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient)
-        const reserverBaseAta = getAssociatedTokenAddressSync(baseMint, poolState, true)
-        const reserverQuoteAta = getAssociatedTokenAddressSync(quoteMint, poolState, true)
 
         const txSignature = await this.program.methods.buy(amount).accounts({
             baseMint, quoteMint,
-            buyer, buyerBaseAta, buyerQuoteAta,
+            buyer,
+            buyerBaseAta: getAssociatedTokenAddressSync(baseMint, buyer, false, baseTokenProgram),
+            // wrapped SOL pools are paid in lamports through the vault
+            buyerQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, buyer),
             poolState,
+            tradeHistory: this.pdas.getTradeHistoryAccount(poolState),
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            userPosition: this.pdas.getUserPositionAccount(poolState, buyer),
             mainState: this.pdas.mainState,
             feeRecipient: mainStateInfo.feeRecipient,
-            feeQuoteAta,
-            reserverBaseAta, reserverQuoteAta,
-            tokenProgram, systemProgram,
+            feeQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient),
+            feeReferrer,
+            feeReferrerAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, feeReferrer, true),
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, poolState, true),
+            tokenProgram, baseTokenProgram, systemProgram,
             associatedTokenProgram,
         }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })]).rpc().catch(buyTxError => {
             debug({ buyTxError })
@@ -214,11 +290,14 @@ export class Connectivity {
         return { Ok: { txSignature } }
     }
 
-    async sell(input: { amount: number, poolId: string }) {
+    // `minQuoteOut` is the least quote, in ui units and after fees, the sell may pay out
+    async sell(input: { amount: number, poolId: string, referrer?: string, minQuoteOut?: number }) {
         const seller = this.provider.publicKey
         if (!seller) return { Err: PumpFunError.WALLET_NOT_FOUND }
         const poolState = getPubkeyFromStr(input.poolId)
         if (!poolState) return { Err: PumpFunError.INVALID_INPUT }
+        const feeReferrer = input.referrer ? getPubkeyFromStr(input.referrer) : NO_REFERRER
+        if (!feeReferrer) return { Err: PumpFunError.INVALID_INPUT }
         const mainStateInfo = await this.program.account.mainState.fetch(this.pdas.mainState)
             .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
         if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
@@ -227,25 +306,30 @@ export class Connectivity {
         if (!poolInfo) return { Err: PumpFunError.POOL_NOT_FOUND }
 
         const { baseMint, quoteMint } = poolInfo;
-        // const accountInfoes = await getMultipleAccountsInfo(this.connection, [baseMint, quoteMint])
-        // if (!accountInfoes) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
-        // const [baseMintAccountInfo, quoteMintAccountInfo] = accountInfoes;
-        // if (!baseMintAccountInfo) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const isNativeQuote = quoteMint.equals(NATIVE_MINT)
         const baseMintDecimals = /* MintLayout.decode(baseMintAccountInfo.data).decimals */ 6
         const sellAmount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.amount, baseMintDecimals).toString()), 8))
-        const sellerBaseAta = getAssociatedTokenAddressSync(baseMint, seller)
-        const sellerQuoteAta = getAssociatedTokenAddressSync(quoteMint, seller)
-        const reserverBaseAta = getAssociatedTokenAddressSync(baseMint, poolState, true)
-        const reserverQuoteAta = getAssociatedTokenAddressSync(quoteMint, poolState, true)
-        const feeQuoteAta = getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient)
-
-        const txSignature = await this.program.methods.sell(sellAmount).accounts({
-            seller, sellerBaseAta, sellerQuoteAta,
+        const minQuoteOut = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.minQuoteOut ?? 0, 9).toString()), 8))
+
+        const txSignature = await this.program.methods.sellWithMinOut(sellAmount, minQuoteOut).accounts({
+            seller,
+            sellerBaseAta: getAssociatedTokenAddressSync(baseMint, seller, false, baseTokenProgram),
+            // wrapped SOL pools pay the seller in lamports
+            sellerQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, seller),
             mainState: this.pdas.mainState, baseMint, quoteMint,
             feeRecipient: mainStateInfo.feeRecipient,
-            feeQuoteAta,
-            poolState, reserverBaseAta, reserverQuoteAta,
-            systemProgram, tokenProgram,
+            feeQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient),
+            feeReferrer,
+            feeReferrerAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, feeReferrer, true),
+            poolState,
+            tradeHistory: this.pdas.getTradeHistoryAccount(poolState),
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            userPosition: this.pdas.getUserPositionAccount(poolState, seller),
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: isNativeQuote ? null : getAssociatedTokenAddressSync(quoteMint, poolState, true),
+            systemProgram, tokenProgram, baseTokenProgram,
             associatedTokenProgram,
         }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })]).rpc().catch(sellTxError => {
             debug({ sellTxError })
@@ -255,42 +339,171 @@ export class Connectivity {
         return { Ok: { txSignature } }
     }
 
-    async withdraw(input: { poolId: string }): Promise<Result<TxPassResult>> {
-        const admin = this.provider.publicKey
-        if (!admin) return { Err: PumpFunError.WALLET_NOT_FOUND }
+    async migrateToAmm(input: { poolId: string }): Promise<Result<TxPassResult>> {
+        const payer = this.provider.publicKey
+        if (!payer) return { Err: PumpFunError.WALLET_NOT_FOUND }
 
         const mainState = this.pdas.mainState
         const mainStateInfo = await this.program.account.mainState.fetch(mainState)
             .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
         if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
-        const owner = mainStateInfo.owner;
+        const feeRecipient = mainStateInfo.feeRecipient
 
         const poolState = getPubkeyFromStr(input.poolId)
         if (!poolState) return { Err: PumpFunError.INVALID_INPUT }
         const poolInfo = await this.program.account.poolState.fetch(poolState)
             .catch((fetchPoolInfoError) => { debug({ fetchPoolInfoError }); return null })
         if (!poolInfo) return { Err: PumpFunError.POOL_NOT_FOUND }
-        const { baseMint, quoteMint } = poolInfo
+        const { baseMint, quoteMint, owner: creator } = poolInfo
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
 
-        const reserverBaseAta = getAssociatedTokenAddressSync(baseMint, poolState, true)
-        const reserverQuoteAta = getAssociatedTokenAddressSync(quoteMint, poolState, true)
+        const ammState = this.pdas.getAmmStateAccount({ baseMint, quoteMint })
+        const lpMint = this.pdas.getLpMintAccount(ammState)
 
-        const adminBaseAta = getAssociatedTokenAddressSync(baseMint, admin)
-        const adminQuoteAta = getAssociatedTokenAddressSync(quoteMint, admin)
+        const txSignature = await this.program.methods.migrateToAmm().accounts({
+            payer, mainState, poolState,
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            baseMint, quoteMint,
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: getAssociatedTokenAddressSync(quoteMint, poolState, true),
+            feeRecipient,
+            feeQuoteAta: getAssociatedTokenAddressSync(quoteMint, feeRecipient),
+            creator,
+            creatorQuoteAta: getAssociatedTokenAddressSync(quoteMint, creator),
+            ammState, lpMint,
+            ammBaseVault: getAssociatedTokenAddressSync(baseMint, ammState, true, baseTokenProgram),
+            ammQuoteVault: getAssociatedTokenAddressSync(quoteMint, ammState, true),
+            lpLockAta: getAssociatedTokenAddressSync(lpMint, ammState, true),
+            associatedTokenProgram, tokenProgram, baseTokenProgram, systemProgram,
+        }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })]).rpc().catch((migrateToAmmError) => {
+            debug({ migrateToAmmError })
+            return null
+        })
+        if (!txSignature) return { Err: PumpFunError.TX_FAILED }
+        return { Ok: { txSignature } }
+    }
 
-        const txSignature = await this.program.methods.withdraw().accounts({
-            admin, mainState, poolState, 
-            owner, 
-            baseMint, quoteMint, 
-            reserverBaseAta, reserverQuoteAta, 
-            adminBaseAta, adminQuoteAta, 
-            systemProgram, tokenProgram,
-            associatedTokenProgram,
-        }).rpc().catch((collectTradingFeeError) => debug({ collectTradingFeeError }));
+    // `remainingAccounts` are forwarded to the dex migrate instruction after the fixed accounts
+    async migrateToDex(input: { poolId: string, dexProgram: string, remainingAccounts?: web3.AccountMeta[] }): Promise<Result<TxPassResult>> {
+        const payer = this.provider.publicKey
+        if (!payer) return { Err: PumpFunError.WALLET_NOT_FOUND }
+        const dexProgram = getPubkeyFromStr(input.dexProgram)
+        if (!dexProgram) return { Err: PumpFunError.INVALID_INPUT }
+
+        const mainState = this.pdas.mainState
+        const mainStateInfo = await this.program.account.mainState.fetch(mainState)
+            .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
+        if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
+        const feeRecipient = mainStateInfo.feeRecipient
+
+        const poolState = getPubkeyFromStr(input.poolId)
+        if (!poolState) return { Err: PumpFunError.INVALID_INPUT }
+        const poolInfo = await this.program.account.poolState.fetch(poolState)
+            .catch((fetchPoolInfoError) => { debug({ fetchPoolInfoError }); return null })
+        if (!poolInfo) return { Err: PumpFunError.POOL_NOT_FOUND }
+        const { baseMint, quoteMint, owner: creator } = poolInfo
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const lpMint = web3.PublicKey.findProgramAddressSync([Seeds.lpMint, baseMint.toBuffer(), quoteMint.toBuffer()], dexProgram)[0]
+
+        const txSignature = await this.program.methods.migrateToDex().accounts({
+            payer, mainState, poolState,
+            solVault: this.pdas.getSolVaultAccount(poolState),
+            baseMint, quoteMint,
+            reserverBaseAta: getAssociatedTokenAddressSync(baseMint, poolState, true, baseTokenProgram),
+            reserverQuoteAta: getAssociatedTokenAddressSync(quoteMint, poolState, true),
+            feeRecipient,
+            feeQuoteAta: getAssociatedTokenAddressSync(quoteMint, feeRecipient),
+            creator,
+            creatorQuoteAta: getAssociatedTokenAddressSync(quoteMint, creator),
+            dexProgram,
+            lpMint,
+            poolLpAta: getAssociatedTokenAddressSync(lpMint, poolState, true),
+            associatedTokenProgram, tokenProgram, baseTokenProgram, systemProgram,
+        }).remainingAccounts(input.remainingAccounts ?? [])
+        .preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })]).rpc().catch((migrateToDexError) => {
+            debug({ migrateToDexError })
+            return null
+        })
+        if (!txSignature) return { Err: PumpFunError.TX_FAILED }
+        return { Ok: { txSignature } }
+    }
+
+    async ammBuy(input: { amount: number, minAmountOut?: number, baseToken: string, quoteToken: string }): Promise<Result<TxPassResult>> {
+        const buyer = this.provider.publicKey
+        if (!buyer) return { Err: PumpFunError.WALLET_NOT_FOUND }
+        const baseMint = getPubkeyFromStr(input.baseToken)
+        const quoteMint = getPubkeyFromStr(input.quoteToken)
+        if (!baseMint || !quoteMint) return { Err: PumpFunError.INVALID_INPUT }
+        const mainStateInfo = await this.program.account.mainState.fetch(this.pdas.mainState)
+            .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
+        if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const ammState = this.pdas.getAmmStateAccount({ baseMint, quoteMint })
+        const amount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.amount, 9).toString()), 8))
+        const minAmountOut = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.minAmountOut ?? 0, 6).toString()), 8))
+
+        const txSignature = await this.program.methods.ammBuy(amount, minAmountOut).accounts({
+            buyer, mainState: this.pdas.mainState,
+            feeRecipient: mainStateInfo.feeRecipient,
+            feeQuoteAta: getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient),
+            ammState, baseMint, quoteMint,
+            buyerBaseAta: getAssociatedTokenAddressSync(baseMint, buyer, false, baseTokenProgram),
+            buyerQuoteAta: getAssociatedTokenAddressSync(quoteMint, buyer),
+            ammBaseVault: getAssociatedTokenAddressSync(baseMint, ammState, true, baseTokenProgram),
+            ammQuoteVault: getAssociatedTokenAddressSync(quoteMint, ammState, true),
+            associatedTokenProgram, tokenProgram, baseTokenProgram, systemProgram,
+        }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })]).rpc().catch(ammBuyTxError => {
+            debug({ ammBuyTxError })
+            return null
+        })
+        if (!txSignature) return { Err: PumpFunError.TX_FAILED }
+        return { Ok: { txSignature } }
+    }
+
+    async ammSell(input: { amount: number, minAmountOut?: number, baseToken: string, quoteToken: string }): Promise<Result<TxPassResult>> {
+        const seller = this.provider.publicKey
+        if (!seller) return { Err: PumpFunError.WALLET_NOT_FOUND }
+        const baseMint = getPubkeyFromStr(input.baseToken)
+        const quoteMint = getPubkeyFromStr(input.quoteToken)
+        if (!baseMint || !quoteMint) return { Err: PumpFunError.INVALID_INPUT }
+        const mainStateInfo = await this.program.account.mainState.fetch(this.pdas.mainState)
+            .catch((fetchMainStateInfoError) => { debug({ fetchMainStateInfoError }); return null })
+        if (!mainStateInfo) return { Err: PumpFunError.FAILED_TO_FETCH_DATA }
+        const baseTokenProgram = await this.getTokenProgram(baseMint)
+        if (!baseTokenProgram) return { Err: PumpFunError.TOKEN_NOT_FOUND }
+        const ammState = this.pdas.getAmmStateAccount({ baseMint, quoteMint })
+        const amount = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.amount, 6).toString()), 8))
+        const minAmountOut = new BN(toBufferBE(BigInt(calcNonDecimalValue(input.minAmountOut ?? 0, 9).toString()), 8))
+
+        const txSignature = await this.program.methods.ammSell(amount, minAmountOut).accounts({
+            seller, mainState: this.pdas.mainState,
+            feeRecipient: mainStateInfo.feeRecipient,
+            feeQuoteAta: getAssociatedTokenAddressSync(quoteMint, mainStateInfo.feeRecipient),
+            ammState, baseMint, quoteMint,
+            sellerBaseAta: getAssociatedTokenAddressSync(baseMint, seller, false, baseTokenProgram),
+            sellerQuoteAta: getAssociatedTokenAddressSync(quoteMint, seller),
+            ammBaseVault: getAssociatedTokenAddressSync(baseMint, ammState, true, baseTokenProgram),
+            ammQuoteVault: getAssociatedTokenAddressSync(quoteMint, ammState, true),
+            associatedTokenProgram, tokenProgram, baseTokenProgram, systemProgram,
+        }).preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })]).rpc().catch(ammSellTxError => {
+            debug({ ammSellTxError })
+            return null
+        })
         if (!txSignature) return { Err: PumpFunError.TX_FAILED }
         return { Ok: { txSignature } }
     }
 
+    // token program owning `mint`, classic spl-token or token-2022
+    async getTokenProgram(mint: web3.PublicKey): Promise<web3.PublicKey | null> {
+        const mintAccountInfo = await this.connection.getAccountInfo(mint)
+            .catch((fetchMintAccountInfoError) => { debug({ fetchMintAccountInfoError }); return null })
+        if (!mintAccountInfo) return null
+        return mintAccountInfo.owner
+    }
+
     async getMainStateInfo(): Promise<MainStateInfo | null> {
         const mainState = this.pdas.mainState
         const mainStateInfo = await this.program.account.mainState.fetch(mainState).catch(fetchMainStateError => {
@@ -323,6 +536,21 @@ export class Connectivity {
         }
     }
 
+    async getUserPosition(poolIdStr: string, owner?: web3.PublicKey): Promise<UserPositionInfo | null> {
+        const poolId = getPubkeyFromStr(poolIdStr)
+        owner = owner ?? this.provider.publicKey
+        if (!poolId || !owner) return null
+        const userPosition = this.pdas.getUserPositionAccount(poolId, owner)
+        const positionInfo = await this.program.account.userPosition.fetchNullable(userPosition).catch(fetchUserPositionError => {
+            debug({ fetchUserPositionError })
+            return null
+        })
+        // no position yet reads as an empty one
+        if (!positionInfo) return { tokensBought: new BN(0), tokensSold: new BN(0), quoteSpent: new BN(0), quoteReceived: new BN(0) }
+        const { tokensBought, tokensSold, quoteSpent, quoteReceived } = positionInfo
+        return { tokensBought, tokensSold, quoteSpent, quoteReceived }
+    }
+
     async getOutputAmountOnBuy(input: { inputAmount: number, poolId: string }): Promise<Result<number>> {
         const mainState = await this.getMainStateInfo();
         if (!mainState) return { Err: PumpFunError.MAIN_STATE_INFO_NOT_FOUND }
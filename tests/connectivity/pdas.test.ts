@@ -1,5 +1,5 @@
 import { web3 } from "@coral-xyz/anchor";
-import { Seeds } from './constants'
+import { PROGRAMS, Seeds } from './constants'
 
 export class Pdas {
     programId: web3.PublicKey
@@ -16,4 +16,37 @@ export class Pdas {
             quoteMint.toBuffer(),
         ], this.programId)[0]
     }
+
+    getSolVaultAccount(poolState: web3.PublicKey) {
+        return web3.PublicKey.findProgramAddressSync([Seeds.solVault, poolState.toBuffer()], this.programId)[0]
+    }
+
+    getTradeHistoryAccount(poolState: web3.PublicKey) {
+        return web3.PublicKey.findProgramAddressSync([Seeds.history, poolState.toBuffer()], this.programId)[0]
+    }
+
+    getUserPositionAccount(poolState: web3.PublicKey, owner: web3.PublicKey) {
+        return web3.PublicKey.findProgramAddressSync([Seeds.position, poolState.toBuffer(), owner.toBuffer()], this.programId)[0]
+    }
+
+    getQuoteConfigAccount(quoteMint: web3.PublicKey) {
+        return web3.PublicKey.findProgramAddressSync([Seeds.quoteConfig, quoteMint.toBuffer()], this.programId)[0]
+    }
+
+    getAmmStateAccount({ baseMint, quoteMint }: { baseMint: web3.PublicKey, quoteMint: web3.PublicKey }) {
+        return web3.PublicKey.findProgramAddressSync([
+            Seeds.amm,
+            baseMint.toBuffer(),
+            quoteMint.toBuffer(),
+        ], this.programId)[0]
+    }
+
+    getLpMintAccount(ammState: web3.PublicKey) {
+        return web3.PublicKey.findProgramAddressSync([Seeds.lpMint, ammState.toBuffer()], this.programId)[0]
+    }
+
+    getMetadataAccount(mint: web3.PublicKey) {
+        const { tokenMetadataProgram } = PROGRAMS
+        return web3.PublicKey.findProgramAddressSync([Seeds.metadata, tokenMetadataProgram.toBuffer(), mint.toBuffer()], tokenMetadataProgram)[0]
+    }
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+mod common;
+
+use common::*;
+use pump_fun::{constants::TOTAL_SUPPLY, error::PumpFunError, pool::PoolState};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+
+async fn start() -> (ProgramTestContext, Keypair) {
+    let mut context = program_test().start_with_context().await;
+    init_main_state(&mut context).await;
+
+    let creator = Keypair::new();
+    fund(&mut context, &creator.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    (context, creator)
+}
+
+#[tokio::test]
+async fn create_pool_takes_the_whole_supply() {
+    let (mut context, creator) = start().await;
+    let base_mint = create_base_mint(&mut context, &creator).await;
+
+    let pool_state = create_sol_pool(&mut context, &creator, base_mint).await;
+    let pool: PoolState = get_anchor_account(&mut context, pool_state).await;
+    assert_eq!(pool.bookkept_base_reserves().unwrap(), TOTAL_SUPPLY);
+}
+
+#[tokio::test]
+async fn create_pool_rejects_supply_kept_outside_the_curve() {
+    let (mut context, creator) = start().await;
+    let base_mint = create_base_mint_with_supply(&mut context, &creator, 2 * TOTAL_SUPPLY).await;
+
+    // the configured supply alone is not enough once the mint holds more
+    let ix = create_sol_pool_ix(&creator.pubkey(), base_mint, TOTAL_SUPPLY);
+    let result = process(&mut context, &[ix], &[&creator]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::IncompleteSupply.into()));
+}
+
+#[tokio::test]
+async fn create_pool_rejects_a_mint_below_the_configured_supply() {
+    let (mut context, creator) = start().await;
+    let base_mint = create_base_mint_with_supply(&mut context, &creator, TOTAL_SUPPLY / 2).await;
+
+    let ix = create_sol_pool_ix(&creator.pubkey(), base_mint, TOTAL_SUPPLY / 2);
+    let result = process(&mut context, &[ix], &[&creator]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::IncompleteSupply.into()));
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::metadata::mpl_token_metadata::{self, accounts::Metadata};
+use common::*;
+use pump_fun::{
+    main_state::MainState,
+    pool::{CreateTokenAndPoolInput, CurveKind, PoolState},
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    program_option::COption,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::StateWithExtensions;
+
+const NAME: &str = "Ten X";
+const SYMBOL: &str = "TENX";
+const URI: &str = "https://10x.fun/tenx.json";
+
+// program-test finds the metadata program in tests/fixtures, see the README for dumping it
+async fn start() -> (ProgramTestContext, Keypair) {
+    let mut program_test = program_test();
+    program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+    let mut context = program_test.start_with_context().await;
+    init_main_state(&mut context).await;
+
+    let creator = Keypair::new();
+    fund(&mut context, &creator.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    (context, creator)
+}
+
+fn create_token_and_pool_ix(creator: &Pubkey, base_mint: &Pubkey, base_token_program: Pubkey) -> Instruction {
+    let quote_mint = spl_token::native_mint::ID;
+    let pool_state = pool_state_key(base_mint, &quote_mint);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts: pump_fun::accounts::ACreateTokenAndPool {
+            creator: *creator,
+            main_state: main_state_key(),
+            pool_state,
+            trade_history: trade_history_key(&pool_state),
+            sol_vault: sol_vault_key(&pool_state),
+            base_mint: *base_mint,
+            quote_mint,
+            quote_config: None,
+            reserver_base_ata: get_associated_token_address_with_program_id(&pool_state, base_mint, &base_token_program),
+            reserver_quote_ata: None,
+            metadata: Metadata::find_pda(base_mint).0,
+            associated_token_program: spl_associated_token_account::ID,
+            token_program: spl_token::ID,
+            base_token_program,
+            system_program: system_program::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: pump_fun::instruction::CreateTokenAndPool {
+            input: CreateTokenAndPoolInput {
+                decimals: 6,
+                name: NAME.to_string(),
+                symbol: SYMBOL.to_string(),
+                uri: URI.to_string(),
+                curve: CurveKind::ConstantProduct,
+            },
+        }
+        .data(),
+    }
+}
+
+// creates a pool over a fresh mint owned by `base_token_program` and checks the mint, the reserve and the metadata
+async fn creates_token_with_metadata(base_token_program: Pubkey) {
+    let (mut context, creator) = start().await;
+    let base_mint = Keypair::new();
+    let ix = create_token_and_pool_ix(&creator.pubkey(), &base_mint.pubkey(), base_token_program);
+    process(&mut context, &[ix], &[&creator, &base_mint]).await.unwrap();
+
+    let main_state: MainState = get_anchor_account(&mut context, main_state_key()).await;
+    let pool_state = pool_state_key(&base_mint.pubkey(), &spl_token::native_mint::ID);
+    let pool: PoolState = get_anchor_account(&mut context, pool_state).await;
+    assert_eq!(pool.owner, creator.pubkey());
+    assert_eq!(pool.bookkept_base_reserves().unwrap(), main_state.total_token_supply);
+
+    // the whole supply sits in the reserve and nobody can mint more
+    let mint_account = get_account(&mut context, base_mint.pubkey()).await;
+    assert_eq!(mint_account.owner, base_token_program);
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data).unwrap().base;
+    assert_eq!(mint.mint_authority, COption::None);
+    assert_eq!(mint.freeze_authority, COption::None);
+    assert_eq!(mint.supply, main_state.total_token_supply);
+    let reserver_base_ata = get_associated_token_address_with_program_id(&pool_state, &base_mint.pubkey(), &base_token_program);
+    assert_eq!(token_amount(&mut context, reserver_base_ata).await, main_state.total_token_supply);
+
+    // the vault is funded up to its rent exempt minimum
+    let sol_vault = get_account(&mut context, sol_vault_key(&pool_state)).await;
+    assert_eq!(sol_vault.lamports, Rent::default().minimum_balance(0));
+
+    // immutable metadata with the pool as the update authority
+    let metadata_account = get_account(&mut context, Metadata::find_pda(&base_mint.pubkey()).0).await;
+    assert_eq!(metadata_account.owner, mpl_token_metadata::ID);
+    let metadata = Metadata::from_bytes(&metadata_account.data).unwrap();
+    assert_eq!(metadata.mint, base_mint.pubkey());
+    assert_eq!(metadata.update_authority, pool_state);
+    assert_eq!(metadata.name.trim_end_matches('\0'), NAME);
+    assert_eq!(metadata.symbol.trim_end_matches('\0'), SYMBOL);
+    assert_eq!(metadata.uri.trim_end_matches('\0'), URI);
+    assert!(!metadata.is_mutable);
+}
+
+#[tokio::test]
+#[ignore = "needs tests/fixtures/mpl_token_metadata.so"]
+async fn create_token_and_pool_writes_metadata_for_a_classic_mint() {
+    creates_token_with_metadata(spl_token::ID).await;
+}
+
+#[tokio::test]
+#[ignore = "needs tests/fixtures/mpl_token_metadata.so"]
+async fn create_token_and_pool_writes_metadata_for_a_token_2022_mint() {
+    creates_token_with_metadata(spl_token_2022::ID).await;
+}
+
+#[tokio::test]
+#[ignore = "needs tests/fixtures/mpl_token_metadata.so"]
+async fn create_token_and_pool_rejects_a_metadata_account_off_the_mint() {
+    let (mut context, creator) = start().await;
+    let base_mint = Keypair::new();
+    let mut ix = create_token_and_pool_ix(&creator.pubkey(), &base_mint.pubkey(), spl_token::ID);
+    let metadata = Metadata::find_pda(&base_mint.pubkey()).0;
+    let meta = ix.accounts.iter_mut().find(|meta| meta.pubkey == metadata).unwrap();
+    meta.pubkey = Metadata::find_pda(&Pubkey::new_unique()).0;
+
+    let result = process(&mut context, &[ix], &[&creator, &base_mint]).await;
+    assert_eq!(custom_error(result), Some(anchor_lang::error::ErrorCode::ConstraintSeeds.into()));
+}
@@ -7,6 +7,10 @@ export async function createToken({ decimals, supply }: { decimals: number, supp
     const owner = provider.publicKey
     const txInfo = await baseSpl.createToken({ mintAuthority: owner, decimals, mintingInfo: { tokenAmount: supply } })
     const { ixs, mintKeypair } = txInfo
+    // pools only accept mints without live authorities
+    const mint = mintKeypair.publicKey
+    ixs.push(baseSpl.revokeAuthority({ mint, currentAuthority: owner, authorityType: 'MINTING' }))
+    ixs.push(baseSpl.revokeAuthority({ mint, currentAuthority: owner, authorityType: 'FREEZING' }))
     const tx = new web3.Transaction().add(...ixs)
     const txSignature = await provider.sendAndConfirm(tx, [mintKeypair])
     return {
@@ -0,0 +1,213 @@
+use anchor_lang::prelude::Pubkey;
+use pump_fun::{
+    math::{calculate_input_amount, calculate_output_amount, calculate_trading_fee},
+    pool::{CurveKind, PoolState, MAX_CURVE_STEPS, MAX_STEPPED_PRICES},
+};
+
+const CASES: usize = 10_000;
+// trading fees are per FEE_PER_DIV percent, 100_000 is a 100% fee
+const FEE_DIV: u128 = 100_000;
+
+// xorshift64*, deterministic so a failing case can be replayed from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // in [low, high], with a random magnitude so small values show up as often as large ones
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        let magnitude = self.next() >> (self.next() % 64);
+        low + magnitude % (high - low).saturating_add(1)
+    }
+}
+
+fn pool(virt_quote: u64, real_quote: u64, real_base: u64, sold_base: u64) -> PoolState {
+    let mut pool = PoolState {
+        owner: Pubkey::default(),
+        konst: 0,
+        base_mint: Pubkey::default(),
+        virt_base_reserves: 0,
+        real_base_reserves: real_base,
+        quote_mint: Pubkey::default(),
+        virt_quote_reserves: virt_quote,
+        real_quote_reserves: real_quote,
+        complete: false,
+        migrated: false,
+        curve: CurveKind::ConstantProduct,
+        init_real_base_reserves: real_base + sold_base,
+        max_price_impact_bps: None,
+        breaker_reference_price: 0,
+        breaker_window_start_slot: 0,
+        breaker_cooldown_until_slot: 0,
+        price_cumulative: 0,
+        last_price_update_ts: 0,
+        total_buy_volume: 0,
+        total_sell_volume: 0,
+        trade_count: 0,
+        total_fees: 0,
+        unique_traders: 0,
+        ath_price: 0,
+        complete_threshold: None,
+        max_buy_limit: None,
+        migration_fee: None,
+        creator_reward: None,
+    };
+    pool.konst = pool.compute_konst().unwrap();
+    pool
+}
+
+const FAMILIES: usize = 5;
+
+// a curve of each family over `supply` tokens, None when the random parameters don't make a valid curve
+fn random_curve(rng: &mut Rng, family: usize, supply: u64) -> Option<CurveKind> {
+    let steps = rng.range(1, MAX_CURVE_STEPS);
+    let step_size = supply.div_ceil(steps).max(1);
+    let start_price = rng.range(1, 10_000_000_000);
+    let curve = match family {
+        0 => CurveKind::ConstantProduct,
+        1 => CurveKind::DualVirtualProduct,
+        2 => CurveKind::Linear { start_price, step_size, increment: rng.range(0, start_price) },
+        3 => CurveKind::Exponential { start_price, step_size, growth_bps: rng.range(0, 5_000) },
+        _ => {
+            let mut prices = [0; MAX_STEPPED_PRICES];
+            let mut price = start_price;
+            for slot in prices.iter_mut().take(rng.range(1, MAX_STEPPED_PRICES as u64) as usize) {
+                *slot = price;
+                price = price.saturating_add(rng.range(0, price));
+            }
+            CurveKind::Stepped { step_size, prices }
+        }
+    };
+    curve.is_valid(supply).then_some(curve)
+}
+
+// a pool of `family` after `sold_base` tokens went out, the step curves hold exactly their buyback
+fn random_pool(rng: &mut Rng, family: usize, real_base: u64, sold_base: u64) -> Option<PoolState> {
+    let curve = random_curve(rng, family, real_base + sold_base)?;
+    let mut pool = pool(rng.range(1_000_000, 1_000_000_000_000), rng.range(0, 100_000_000_000), real_base, sold_base);
+    pool.curve = curve;
+    match curve {
+        CurveKind::ConstantProduct => {}
+        CurveKind::DualVirtualProduct => pool.virt_base_reserves = rng.range(0, 1_000_000_000_000_000),
+        _ => pool.real_quote_reserves = curve.quote_sell(sold_base, sold_base).ok()?,
+    }
+    pool.konst = pool.compute_konst().ok()?;
+    Some(pool)
+}
+
+#[test]
+fn output_amount_never_lowers_the_product() {
+    let mut rng = Rng(0x5eed_0001);
+    for case in 0..CASES {
+        let input_reserve = rng.range(1, u64::MAX / 2);
+        let output_reserve = rng.range(0, u64::MAX);
+        let input_amount = rng.range(0, u64::MAX / 2);
+        let output = calculate_output_amount(input_amount, input_reserve, output_reserve).unwrap();
+        assert!(output <= output_reserve, "case {case}: output above the reserve");
+        let product_before = input_reserve as u128 * output_reserve as u128;
+        let product_after = (input_reserve as u128 + input_amount as u128) * (output_reserve - output) as u128;
+        assert!(product_after >= product_before, "case {case}: k went down");
+    }
+}
+
+#[test]
+fn trading_fee_rounds_up_and_never_exceeds_the_amount() {
+    let mut rng = Rng(0x5eed_0002);
+    for case in 0..CASES {
+        let fee_rate = rng.range(0, FEE_DIV as u64);
+        let amount = rng.range(0, u64::MAX);
+        let fee = calculate_trading_fee(fee_rate, amount).unwrap() as u128;
+        let exact = amount as u128 * fee_rate as u128;
+        assert!(fee * FEE_DIV >= exact, "case {case}: fee rounded down");
+        assert!(fee * FEE_DIV < exact + FEE_DIV, "case {case}: fee rounded up by more than one unit");
+        assert!(fee <= amount as u128, "case {case}: fee above the amount");
+    }
+}
+
+// the constant product curves must not lose k either, the step curves keep their buyback by construction
+fn check_konst(pool: &PoolState, before: &PoolState) {
+    if matches!(pool.curve, CurveKind::ConstantProduct | CurveKind::DualVirtualProduct) {
+        assert!(pool.compute_konst().unwrap() >= before.konst, "{:?}: k went down", pool.curve);
+    }
+}
+
+#[test]
+fn buy_then_sell_never_extracts_value() {
+    let mut rng = Rng(0x5eed_0003);
+    for family in 0..FAMILIES {
+        let mut checked = 0;
+        for _ in 0..CASES {
+            let real_base = rng.range(1_000_000, 1_000_000_000_000_000);
+            let sold_base = rng.range(0, 1_000_000_000_000_000);
+            let Some(mut pool) = random_pool(&mut rng, family, real_base, sold_base) else { continue };
+            let trading_fee = rng.range(0, 10_000);
+            let amount = rng.range(1, 100_000_000_000);
+            let before = pool.clone();
+
+            let Ok(buy) = pool.apply_buy(trading_fee, amount, false, None) else { continue };
+            check_konst(&pool, &before);
+            let Ok(sell) = pool.apply_sell(trading_fee, buy.amount_out, false, None) else { continue };
+
+            assert!(sell.amount_out <= amount, "{:?}: round trip of {amount} paid out {}", pool.curve, sell.amount_out);
+            assert!(pool.real_quote_reserves >= before.real_quote_reserves, "{:?}: pool lost quote on a round trip of {amount}", pool.curve);
+            assert_eq!(pool.real_base_reserves, before.real_base_reserves);
+            check_konst(&pool, &before);
+            checked += 1;
+        }
+        assert!(checked > CASES / 4, "family {family}: only {checked} round trips went through");
+    }
+}
+
+#[test]
+fn sell_then_buy_never_extracts_value() {
+    let mut rng = Rng(0x5eed_0004);
+    for family in 0..FAMILIES {
+        let mut checked = 0;
+        for _ in 0..CASES {
+            let real_base = rng.range(1_000_000, 1_000_000_000_000_000);
+            let sold_base = rng.range(1, 1_000_000_000_000_000);
+            let Some(mut pool) = random_pool(&mut rng, family, real_base, sold_base) else { continue };
+            if family == 0 && pool.real_quote_reserves == 0 {
+                continue;
+            }
+            let trading_fee = rng.range(0, 10_000);
+            let amount = rng.range(1, sold_base);
+            let before = pool.clone();
+
+            let Ok(sell) = pool.apply_sell(trading_fee, amount, false, None) else { continue };
+            let Ok(buy) = pool.apply_buy(trading_fee, sell.amount_out, false, None) else { continue };
+
+            assert!(buy.amount_out <= amount, "{:?}: round trip of {amount} tokens bought back {}", pool.curve, buy.amount_out);
+            assert!(pool.real_base_reserves >= before.real_base_reserves);
+            check_konst(&pool, &before);
+            checked += 1;
+        }
+        assert!(checked > CASES / 8, "family {family}: only {checked} round trips went through");
+    }
+}
+
+#[test]
+fn input_amount_rounds_up_to_the_smallest_sufficient_input() {
+    let mut rng = Rng(0x5eed_0005);
+    let mut checked = 0;
+    for case in 0..CASES {
+        let input_reserve = rng.range(1, u64::MAX / 2);
+        let output_reserve = rng.range(2, u64::MAX);
+        let output_amount = rng.range(1, output_reserve - 1);
+        let Ok(input) = calculate_input_amount(output_amount, input_reserve, output_reserve) else { continue };
+        if input > u64::MAX / 2 {
+            continue;
+        }
+        let output = calculate_output_amount(input, input_reserve, output_reserve).unwrap();
+        assert!(output >= output_amount, "case {case}: input {input} only buys {output} of {output_amount}");
+        let short = calculate_output_amount(input - 1, input_reserve, output_reserve).unwrap();
+        assert!(short < output_amount, "case {case}: input {input} is not the smallest");
+        checked += 1;
+    }
+    assert!(checked > CASES / 2, "only {checked} inputs were computable");
+}
@@ -0,0 +1,169 @@
+mod common;
+mod mock_dex;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::*;
+use pump_fun::{error::PumpFunError, pool::{PoolState, PRICE_PRECISION}};
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account};
+
+struct Graduated {
+    context: ProgramTestContext,
+    creator: Keypair,
+    base_mint: Pubkey,
+    pool_state: Pubkey,
+    dex_base_vault: Pubkey,
+    dex_quote_vault: Pubkey,
+}
+
+// a wrapped SOL pool bought out past the completion threshold, with empty mock dex vaults
+async fn graduated_pool() -> Graduated {
+    let mut program_test = program_test();
+    program_test.add_program("mock_dex", mock_dex::ID, processor!(mock_dex::process_instruction));
+    let mut context = program_test.start_with_context().await;
+
+    init_main_state(&mut context).await;
+    update_main_state(&mut context, |input| input.max_buy_limit = 100 * LAMPORTS_PER_SOL).await;
+
+    let creator = Keypair::new();
+    fund(&mut context, &creator.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    let base_mint = create_base_mint(&mut context, &creator).await;
+    let pool_state = create_sol_pool(&mut context, &creator, base_mint).await;
+
+    let buyer = Keypair::new();
+    fund(&mut context, &buyer.pubkey(), 100 * LAMPORTS_PER_SOL).await;
+    let fee_recipient = context.payer.pubkey();
+    let ix = buy_ix(&buyer.pubkey(), &fee_recipient, &INCINERATOR, base_mint, 80 * LAMPORTS_PER_SOL);
+    process(&mut context, &[ix], &[&buyer]).await.unwrap();
+    let pool: PoolState = get_anchor_account(&mut context, pool_state).await;
+    assert!(pool.complete);
+
+    let dex_authority = Pubkey::new_unique();
+    let payer = context.payer.pubkey();
+    let ixs = [
+        create_associated_token_account(&payer, &dex_authority, &base_mint, &spl_token::ID),
+        create_associated_token_account(&payer, &dex_authority, &spl_token::native_mint::ID, &spl_token::ID),
+    ];
+    process(&mut context, &ixs, &[]).await.unwrap();
+
+    Graduated {
+        context,
+        creator,
+        base_mint,
+        pool_state,
+        dex_base_vault: get_associated_token_address(&dex_authority, &base_mint),
+        dex_quote_vault: get_associated_token_address(&dex_authority, &spl_token::native_mint::ID),
+    }
+}
+
+fn migrate_to_dex_ix(graduated: &Graduated, dex_program: Pubkey, lp_mint: Pubkey) -> Instruction {
+    let payer = graduated.context.payer.pubkey();
+    let quote_mint = spl_token::native_mint::ID;
+    let pool_state = graduated.pool_state;
+    let mut accounts = pump_fun::accounts::AMigrateToDex {
+        payer,
+        main_state: main_state_key(),
+        pool_state,
+        sol_vault: sol_vault_key(&pool_state),
+        base_mint: graduated.base_mint,
+        quote_mint,
+        reserver_base_ata: get_associated_token_address(&pool_state, &graduated.base_mint),
+        reserver_quote_ata: get_associated_token_address(&pool_state, &quote_mint),
+        fee_recipient: payer,
+        fee_quote_ata: get_associated_token_address(&payer, &quote_mint),
+        creator: graduated.creator.pubkey(),
+        creator_quote_ata: get_associated_token_address(&graduated.creator.pubkey(), &quote_mint),
+        dex_program,
+        lp_mint,
+        pool_lp_ata: get_associated_token_address(&pool_state, &lp_mint),
+        associated_token_program: spl_associated_token_account::ID,
+        token_program: spl_token::ID,
+        base_token_program: spl_token::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend([
+        AccountMeta::new(graduated.dex_base_vault, false),
+        AccountMeta::new(graduated.dex_quote_vault, false),
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+    ]);
+    Instruction {
+        program_id: pump_fun::ID,
+        accounts,
+        data: pump_fun::instruction::MigrateToDex {}.data(),
+    }
+}
+
+async fn allow_mock_dex(context: &mut ProgramTestContext) {
+    update_main_state(context, |input| {
+        input.dex_allowlist = Some([mock_dex::ID, Pubkey::default(), Pubkey::default(), Pubkey::default()])
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn migrate_to_dex_moves_reserves_and_burns_lp() {
+    let mut graduated = graduated_pool().await;
+    allow_mock_dex(&mut graduated.context).await;
+
+    let pool: PoolState = get_anchor_account(&mut graduated.context, graduated.pool_state).await;
+    let bookkept_base = pool.virt_base_reserves + pool.real_base_reserves;
+    let quote_amount = pool.real_quote_reserves;
+    let closing_price = pool.closing_price().unwrap();
+    let base_amount = pool.graduation_base_amount(quote_amount).unwrap();
+    let supply_before = mint_supply(&mut graduated.context, graduated.base_mint).await;
+
+    let lp_mint = mock_dex::lp_mint_key(&graduated.base_mint, &spl_token::native_mint::ID);
+    let ix = migrate_to_dex_ix(&graduated, mock_dex::ID, lp_mint);
+    process(&mut graduated.context, &[ix], &[]).await.unwrap();
+
+    let pool: PoolState = get_anchor_account(&mut graduated.context, graduated.pool_state).await;
+    assert!(pool.migrated);
+    assert_eq!(pool.real_base_reserves + pool.virt_base_reserves + pool.real_quote_reserves, 0);
+
+    // the dex received the reserves at the closing price of the curve
+    let dex_base = token_amount(&mut graduated.context, graduated.dex_base_vault).await;
+    let dex_quote = token_amount(&mut graduated.context, graduated.dex_quote_vault).await;
+    assert_eq!(dex_base, base_amount);
+    assert_eq!(dex_quote, quote_amount);
+    let dex_price = dex_quote as u128 * PRICE_PRECISION / dex_base as u128;
+    assert!(dex_price.abs_diff(closing_price) * 10_000 <= closing_price);
+
+    // everything the dex did not take is burned, and so is every lp token
+    let supply_after = mint_supply(&mut graduated.context, graduated.base_mint).await;
+    assert_eq!(supply_before - supply_after, bookkept_base - base_amount);
+    assert_eq!(mint_supply(&mut graduated.context, lp_mint).await, 0);
+    let pool_lp_ata = get_associated_token_address(&graduated.pool_state, &lp_mint);
+    assert_eq!(token_amount(&mut graduated.context, pool_lp_ata).await, 0);
+}
+
+#[tokio::test]
+async fn migrate_to_dex_rejects_a_program_off_the_allowlist() {
+    let mut graduated = graduated_pool().await;
+
+    let lp_mint = mock_dex::lp_mint_key(&graduated.base_mint, &spl_token::native_mint::ID);
+    let ix = migrate_to_dex_ix(&graduated, mock_dex::ID, lp_mint);
+    let result = process(&mut graduated.context, &[ix], &[]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::DexNotAllowed.into()));
+}
+
+#[tokio::test]
+async fn migrate_to_dex_rejects_an_lp_mint_outside_the_dex() {
+    let mut graduated = graduated_pool().await;
+    allow_mock_dex(&mut graduated.context).await;
+
+    let ix = migrate_to_dex_ix(&graduated, mock_dex::ID, Pubkey::new_unique());
+    let result = process(&mut graduated.context, &[ix], &[]).await;
+    assert_eq!(custom_error(result), Some(anchor_lang::error::ErrorCode::ConstraintSeeds.into()));
+
+    let pool: PoolState = get_anchor_account(&mut graduated.context, graduated.pool_state).await;
+    assert!(!pool.migrated);
+}
@@ -0,0 +1,117 @@
+// dex for the graduation tests, takes both reserves and mints sqrt(base * quote) lp tokens to the pool
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+pub const ID: Pubkey = solana_program::pubkey!("MockDex111111111111111111111111111111111111");
+pub const LP_MINT_SEED: &[u8] = b"lp_mint";
+
+pub fn lp_mint_key(base_mint: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LP_MINT_SEED, base_mint.as_ref(), quote_mint.as_ref()], &ID).0
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() != 24 || data[..8] != hash(b"global:migrate").to_bytes()[..8] {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let base_amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let quote_amount = u64::from_le_bytes(data[16..24].try_into().unwrap());
+
+    let iter = &mut accounts.iter();
+    let pool_state = next_account_info(iter)?;
+    let base_mint = next_account_info(iter)?;
+    let quote_mint = next_account_info(iter)?;
+    let reserver_base_ata = next_account_info(iter)?;
+    let reserver_quote_ata = next_account_info(iter)?;
+    let lp_mint = next_account_info(iter)?;
+    let pool_lp_ata = next_account_info(iter)?;
+    let token_program = next_account_info(iter)?;
+    let base_token_program = next_account_info(iter)?;
+    let dex_base_vault = next_account_info(iter)?;
+    let dex_quote_vault = next_account_info(iter)?;
+    let payer = next_account_info(iter)?;
+    let system_program = next_account_info(iter)?;
+    let associated_token_program = next_account_info(iter)?;
+
+    if !pool_state.is_signer || !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let base_decimals = StateWithExtensions::<Mint>::unpack(&base_mint.try_borrow_data()?)?.base.decimals;
+    invoke(
+        &spl_token_2022::instruction::transfer_checked(
+            base_token_program.key,
+            reserver_base_ata.key,
+            base_mint.key,
+            dex_base_vault.key,
+            pool_state.key,
+            &[],
+            base_amount,
+            base_decimals,
+        )?,
+        &[reserver_base_ata.clone(), base_mint.clone(), dex_base_vault.clone(), pool_state.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::transfer(token_program.key, reserver_quote_ata.key, dex_quote_vault.key, pool_state.key, &[], quote_amount)?,
+        &[reserver_quote_ata.clone(), dex_quote_vault.clone(), pool_state.clone()],
+    )?;
+
+    let (lp_mint_key, bump) =
+        Pubkey::find_program_address(&[LP_MINT_SEED, base_mint.key.as_ref(), quote_mint.key.as_ref()], program_id);
+    if lp_mint_key != *lp_mint.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let lp_mint_seeds: &[&[u8]] = &[LP_MINT_SEED, base_mint.key.as_ref(), quote_mint.key.as_ref(), &[bump]];
+    let mint_len = spl_token::state::Mint::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            lp_mint.key,
+            Rent::get()?.minimum_balance(mint_len),
+            mint_len as u64,
+            token_program.key,
+        ),
+        &[payer.clone(), lp_mint.clone(), system_program.clone()],
+        &[lp_mint_seeds],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(token_program.key, lp_mint.key, lp_mint.key, None, 9)?,
+        std::slice::from_ref(lp_mint),
+    )?;
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account(
+            payer.key,
+            pool_state.key,
+            lp_mint.key,
+            token_program.key,
+        ),
+        &[
+            payer.clone(),
+            pool_lp_ata.clone(),
+            pool_state.clone(),
+            lp_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+
+    let lp_amount = ((base_amount as u128 * quote_amount as u128) as f64).sqrt() as u64;
+    invoke_signed(
+        &spl_token::instruction::mint_to(token_program.key, lp_mint.key, pool_lp_ata.key, lp_mint.key, &[], lp_amount)?,
+        &[lp_mint.clone(), pool_lp_ata.clone()],
+        &[lp_mint_seeds],
+    )?;
+    Ok(())
+}
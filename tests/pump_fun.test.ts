@@ -102,6 +102,34 @@ describe("pump_fun", () => {
     log(`Sell Tx Sign: ${res.Ok.txSignature}`)
   })
 
+  it("buy then sell never extracts value", async () => {
+    const poolId = commonState.poolId
+    if (!poolId) throw "pool id not found"
+    for (const amount of [0.000_001_001, 0.013, 0.7]) {
+      await sleep(3_000)
+      const poolBefore = await userConnectivity.getPoolInfo(poolId)
+      const positionBefore = await userConnectivity.getUserPosition(poolId)
+      const boughtTokens = (await userConnectivity.getOutputAmountOnBuy({ inputAmount: amount, poolId })).Ok
+      if (!boughtTokens) throw "quote failed"
+      const buyRes = await userConnectivity.buy({ poolId, amount })
+      if (!buyRes.Ok) throw "buy fail"
+      await sleep(3_000)
+      const sellRes = await userConnectivity.sell({ poolId, amount: boughtTokens })
+      if (!sellRes.Ok) throw "sell fail"
+      await sleep(3_000)
+      const poolAfter = await userConnectivity.getPoolInfo(poolId)
+      const positionAfter = await userConnectivity.getUserPosition(poolId)
+      if (!poolBefore || !poolAfter) throw "pool info not found"
+      if (!positionBefore || !positionAfter) throw "user position not found"
+      // quote amounts of the position are exact and exclude tx fees and ata rent, unlike the wallet balance
+      const spent = positionAfter.quoteSpent.sub(positionBefore.quoteSpent)
+      const received = positionAfter.quoteReceived.sub(positionBefore.quoteReceived)
+      assert.isTrue(spent.gtn(0), `round trip of ${amount} SOL recorded no buy`)
+      assert.isTrue(received.lt(spent), `round trip of ${amount} SOL returned ${received} for ${spent}`)
+      assert.isTrue(poolAfter.realQuoteReserves.gte(poolBefore.realQuoteReserves), `pool lost quote on a ${amount} SOL round trip`)
+    }
+  })
+
   it("update trading fee", async () => {
     const res = await connectivity.updateMainState({ tradingFee: 0.5 })
     if (res.Err) {
@@ -145,12 +173,12 @@ describe("pump_fun", () => {
     log(`Sell Tx Sign: ${res.Ok.txSignature}`)
   })
   
-  it("Withdraw (BondingCurveIncomplete: Fail)", async () => {
+  it("Migrate to amm (BondingCurveIncomplete: Fail)", async () => {
     await sleep(3_000)
     const poolId = commonState.poolId
     if (!poolId) throw "pool id not found"
-    const res = await adminConnectivity.withdraw({ poolId })
-    if (res.Ok) assert.fail("Withdraw should be failed (BondingCurveIncomplete")
+    const res = await userConnectivity.migrateToAmm({ poolId })
+    if (res.Ok) assert.fail("Migration should be failed (BondingCurveIncomplete)")
   })
   
   it("buy3", async () => {
@@ -174,23 +202,16 @@ describe("pump_fun", () => {
     // log(`poolStateInfo.realQuoteReserves: ${poolStateInfo?.realQuoteReserves}`);
   })
 
-  it("Withdraw", async () => {
+  it("Migrate to amm", async () => {
     await sleep(3_000)
     const poolId = commonState.poolId
     if (!poolId) throw "pool id not found"
-    const res = await adminConnectivity.withdraw({ poolId })
+    const res = await userConnectivity.migrateToAmm({ poolId })
     if (res.Err) {
       log(`Error: ${res.Err}`)
-      throw "Withdraw tx Error"
+      throw "Migrate to amm tx Error"
     }
-    if (!res.Ok) throw "withdraw failed"
-    log(`Withdraw Tx Sign: ${res.Ok.txSignature}`)
+    if (!res.Ok) throw "migration failed"
+    log(`Migrate Tx Sign: ${res.Ok.txSignature}`)
   })
-
-  // it("Withdraw (Unauthorised: Fail)", async () => {
-  //   const poolId = commonState.poolId
-  //   if (!poolId) throw "pool id not found"
-  //   const res = await userConnectivity.withdraw({ poolId })
-  //   if (res.Ok) assert.fail("Tx should be failed (Unauthorised access)")
-  // })
 });
@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use pump_fun::{error::PumpFunError, main_state::MainState, pool::PoolState};
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+#[tokio::test]
+async fn sell_with_min_out_enforces_min_quote_out() {
+    let mut context = program_test().start_with_context().await;
+    init_main_state(&mut context).await;
+    let fee_recipient = context.payer.pubkey();
+
+    let creator = Keypair::new();
+    fund(&mut context, &creator.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    let base_mint = create_base_mint(&mut context, &creator).await;
+    let pool_state = create_sol_pool(&mut context, &creator, base_mint).await;
+
+    let seller = Keypair::new();
+    fund(&mut context, &seller.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    let ix = buy_ix(&seller.pubkey(), &fee_recipient, &INCINERATOR, base_mint, LAMPORTS_PER_SOL);
+    process(&mut context, &[ix], &[&seller]).await.unwrap();
+    let bought = token_amount(&mut context, get_associated_token_address(&seller.pubkey(), &base_mint)).await;
+
+    let main_state: MainState = get_anchor_account(&mut context, main_state_key()).await;
+    let mut pool: PoolState = get_anchor_account(&mut context, pool_state).await;
+    let expected = pool.apply_sell(main_state.trading_fee, bought, false, None).unwrap().amount_out;
+
+    let ix = sell_with_min_out_ix(&seller.pubkey(), &fee_recipient, &INCINERATOR, base_mint, bought, expected + 1);
+    let result = process(&mut context, &[ix], &[&seller]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::SlippageExceeded.into()));
+
+    let lamports_before = context.banks_client.get_balance(seller.pubkey()).await.unwrap();
+    let ix = sell_with_min_out_ix(&seller.pubkey(), &fee_recipient, &INCINERATOR, base_mint, bought, expected);
+    process(&mut context, &[ix], &[&seller]).await.unwrap();
+    let lamports_after = context.banks_client.get_balance(seller.pubkey()).await.unwrap();
+    // the context payer covers the transaction fee
+    assert_eq!(lamports_after - lamports_before, expected);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::*;
+use pump_fun::pool::PoolState;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+struct Market {
+    context: ProgramTestContext,
+    buyer: Keypair,
+    fee_recipient: Pubkey,
+    base_mint: Pubkey,
+    pool_state: Pubkey,
+}
+
+// a fresh wrapped SOL pool whose fee recipient holds `fee_recipient_lamports`
+async fn market(fee_recipient_lamports: u64) -> Market {
+    let mut context = program_test().start_with_context().await;
+    init_main_state(&mut context).await;
+
+    let fee_recipient = Pubkey::new_unique();
+    if fee_recipient_lamports > 0 {
+        fund(&mut context, &fee_recipient, fee_recipient_lamports).await;
+    }
+    update_main_state(&mut context, |input| input.fee_recipient = fee_recipient).await;
+
+    let creator = Keypair::new();
+    fund(&mut context, &creator.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    let base_mint = create_base_mint(&mut context, &creator).await;
+    let pool_state = create_sol_pool(&mut context, &creator, base_mint).await;
+
+    let buyer = Keypair::new();
+    fund(&mut context, &buyer.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    Market { context, buyer, fee_recipient, base_mint, pool_state }
+}
+
+async fn balance(context: &mut ProgramTestContext, key: Pubkey) -> u64 {
+    context.banks_client.get_balance(key).await.unwrap()
+}
+
+// lamports in the vault above its rent exempt minimum and the bookkept reserves
+async fn vault_excess(context: &mut ProgramTestContext, pool_state: Pubkey) -> u64 {
+    let pool: PoolState = get_anchor_account(context, pool_state).await;
+    let vault = balance(context, sol_vault_key(&pool_state)).await;
+    vault - Rent::default().minimum_balance(0) - pool.bookkept_quote_reserves().unwrap()
+}
+
+#[tokio::test]
+async fn buy_pays_both_shares_to_funded_wallets() {
+    let mut market = market(LAMPORTS_PER_SOL).await;
+    let referrer = Pubkey::new_unique();
+    fund(&mut market.context, &referrer, LAMPORTS_PER_SOL).await;
+
+    let ix = buy_ix(&market.buyer.pubkey(), &market.fee_recipient, &referrer, market.base_mint, LAMPORTS_PER_SOL / 10);
+    process(&mut market.context, &[ix], &[&market.buyer]).await.unwrap();
+
+    let pool: PoolState = get_anchor_account(&mut market.context, market.pool_state).await;
+    let referrer_fee = pool.total_fees / 2;
+    assert_eq!(balance(&mut market.context, referrer).await, LAMPORTS_PER_SOL + referrer_fee);
+    assert_eq!(balance(&mut market.context, market.fee_recipient).await, LAMPORTS_PER_SOL + pool.total_fees - referrer_fee);
+}
+
+#[tokio::test]
+async fn buy_redirects_a_referrer_share_below_rent_to_the_fee_recipient() {
+    let mut market = market(LAMPORTS_PER_SOL).await;
+    // a fresh referrer wallet can't hold the half of a 0.001 SOL fee
+    let referrer = Pubkey::new_unique();
+
+    let ix = buy_ix(&market.buyer.pubkey(), &market.fee_recipient, &referrer, market.base_mint, LAMPORTS_PER_SOL / 10);
+    process(&mut market.context, &[ix], &[&market.buyer]).await.unwrap();
+
+    let pool: PoolState = get_anchor_account(&mut market.context, market.pool_state).await;
+    assert!(pool.total_fees / 2 < Rent::default().minimum_balance(0));
+    assert_eq!(balance(&mut market.context, referrer).await, 0);
+    assert_eq!(balance(&mut market.context, market.fee_recipient).await, LAMPORTS_PER_SOL + pool.total_fees);
+    assert_eq!(vault_excess(&mut market.context, market.pool_state).await, 0);
+}
+
+#[tokio::test]
+async fn buy_leaves_a_fee_nobody_can_hold_in_the_vault() {
+    let mut market = market(0).await;
+    let referrer = Pubkey::new_unique();
+
+    let ix = buy_ix(&market.buyer.pubkey(), &market.fee_recipient, &referrer, market.base_mint, LAMPORTS_PER_SOL / 20);
+    process(&mut market.context, &[ix], &[&market.buyer]).await.unwrap();
+
+    let pool: PoolState = get_anchor_account(&mut market.context, market.pool_state).await;
+    assert!(pool.total_fees > 0);
+    assert_eq!(balance(&mut market.context, referrer).await, 0);
+    assert_eq!(balance(&mut market.context, market.fee_recipient).await, 0);
+    assert_eq!(vault_excess(&mut market.context, market.pool_state).await, pool.total_fees);
+}
+
+#[tokio::test]
+async fn sell_leaves_a_fee_nobody_can_hold_in_the_vault() {
+    let mut market = market(0).await;
+    let referrer = Pubkey::new_unique();
+    let buyer = market.buyer.pubkey();
+
+    let ix = buy_ix(&buyer, &market.fee_recipient, &INCINERATOR, market.base_mint, LAMPORTS_PER_SOL / 20);
+    process(&mut market.context, &[ix], &[&market.buyer]).await.unwrap();
+    let bought = token_amount(&mut market.context, get_associated_token_address(&buyer, &market.base_mint)).await;
+
+    let ix = sell_ix(&buyer, &market.fee_recipient, &referrer, market.base_mint, bought);
+    process(&mut market.context, &[ix], &[&market.buyer]).await.unwrap();
+
+    let pool: PoolState = get_anchor_account(&mut market.context, market.pool_state).await;
+    assert_eq!(pool.trade_count, 2);
+    assert_eq!(balance(&mut market.context, referrer).await, 0);
+    assert_eq!(balance(&mut market.context, market.fee_recipient).await, 0);
+    assert_eq!(vault_excess(&mut market.context, market.pool_state).await, pool.total_fees);
+}
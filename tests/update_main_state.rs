@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use pump_fun::{constants::BPS_DIV, error::PumpFunError, main_state::MainState};
+use solana_program_test::ProgramTestContext;
+
+async fn start() -> ProgramTestContext {
+    let mut context = program_test().start_with_context().await;
+    init_main_state(&mut context).await;
+    context
+}
+
+#[tokio::test]
+async fn update_main_state_bounds_the_price_impact_limit() {
+    let mut context = start().await;
+
+    let ix = update_main_state_ix(&mut context, |input| input.max_price_impact_bps = Some(BPS_DIV as u64 + 1)).await;
+    let result = process(&mut context, &[ix], &[]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::InvalidPriceImpactLimit.into()));
+
+    update_main_state(&mut context, |input| input.max_price_impact_bps = Some(BPS_DIV as u64)).await;
+    let main_state: MainState = get_anchor_account(&mut context, main_state_key()).await;
+    assert_eq!(main_state.max_price_impact_bps, BPS_DIV as u64);
+}
+
+#[tokio::test]
+async fn update_main_state_rejects_a_breaker_without_window_or_cooldown() {
+    let mut context = start().await;
+
+    let ix = update_main_state_ix(&mut context, |input| {
+        input.circuit_breaker_threshold_bps = Some(BPS_DIV as u64 + 1);
+        input.circuit_breaker_window_slots = Some(10);
+        input.circuit_breaker_cooldown_slots = Some(10);
+    })
+    .await;
+    let result = process(&mut context, &[ix], &[]).await;
+    assert_eq!(custom_error(result), Some(PumpFunError::InvalidCircuitBreaker.into()));
+
+    for (window, cooldown) in [(0, 10), (10, 0)] {
+        let ix = update_main_state_ix(&mut context, |input| {
+            input.circuit_breaker_threshold_bps = Some(1_000);
+            input.circuit_breaker_window_slots = Some(window);
+            input.circuit_breaker_cooldown_slots = Some(cooldown);
+        })
+        .await;
+        let result = process(&mut context, &[ix], &[]).await;
+        assert_eq!(custom_error(result), Some(PumpFunError::InvalidCircuitBreaker.into()));
+    }
+
+    update_main_state(&mut context, |input| {
+        input.circuit_breaker_threshold_bps = Some(1_000);
+        input.circuit_breaker_window_slots = Some(10);
+        input.circuit_breaker_cooldown_slots = Some(10);
+    })
+    .await;
+    let main_state: MainState = get_anchor_account(&mut context, main_state_key()).await;
+    assert_eq!(main_state.circuit_breaker_threshold_bps, 1_000);
+}